@@ -0,0 +1,212 @@
+//! Accessibility options, picked on the settings screen and saved: shapes
+//! drawn over food so it doesn't rely on colour alone, larger text, and
+//! reduced motion for the shake, flashes and particles.
+
+use bevy::prelude::*;
+use enum_iterator::Sequence;
+
+use crate::{
+    game::Food,
+    storage,
+    theme::{Role, Themed},
+};
+
+const PATTERNS_KEY: &str = "settings.patterns";
+const TEXT_SCALE_KEY: &str = "settings.text_scale";
+const REDUCED_MOTION_KEY: &str = "settings.reduced_motion";
+
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Accessibility::load());
+
+        app.add_systems(
+            PostUpdate,
+            (
+                add_patterns.run_if(patterns_shown),
+                remove_patterns.run_if(not(patterns_shown)),
+                scale_text,
+            ),
+        );
+    }
+}
+
+/// How much larger than designed UI text is drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Sequence)]
+pub(crate) enum TextScale {
+    #[default]
+    Normal,
+    Large,
+    Larger,
+}
+
+impl TextScale {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Self::Normal => "Normal",
+            Self::Large => "Large",
+            Self::Larger => "Larger",
+        }
+    }
+
+    fn factor(&self) -> f32 {
+        match self {
+            Self::Normal => 1.0,
+            Self::Large => 1.2,
+            Self::Larger => 1.4,
+        }
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Large => "large",
+            Self::Larger => "larger",
+        }
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub(crate) struct Accessibility {
+    pub(crate) patterns: bool,
+    pub(crate) text_scale: TextScale,
+    /// No camera shake, flashing or particles.
+    pub(crate) reduced_motion: bool,
+}
+
+impl Accessibility {
+    fn load() -> Self {
+        let flag = |key| storage::load(key).is_some_and(|value| value.trim() == "on");
+        let text_scale = storage::load(TEXT_SCALE_KEY)
+            .and_then(|value| {
+                enum_iterator::all::<TextScale>().find(|scale| scale.key() == value.trim())
+            })
+            .unwrap_or_default();
+        Self {
+            patterns: flag(PATTERNS_KEY),
+            text_scale,
+            reduced_motion: flag(REDUCED_MOTION_KEY),
+        }
+    }
+
+    pub(crate) fn save(&self) {
+        let flag = |value: bool| if value { "on" } else { "off" };
+        let saved = [
+            (PATTERNS_KEY, flag(self.patterns)),
+            (TEXT_SCALE_KEY, self.text_scale.key()),
+            (REDUCED_MOTION_KEY, flag(self.reduced_motion)),
+        ]
+        .into_iter()
+        .try_for_each(|(key, value)| storage::save(key, value));
+        if let Err(err) = saved {
+            warn!("Could not save the accessibility settings: {err}");
+        }
+    }
+}
+
+/// Run condition for effects left out under reduced motion.
+pub(crate) fn full_motion(accessibility: Res<Accessibility>) -> bool {
+    !accessibility.reduced_motion
+}
+
+fn patterns_shown(accessibility: Res<Accessibility>) -> bool {
+    accessibility.patterns
+}
+
+/// Pieces of a shape, as offset and size within the cell.
+type Shape = &'static [(Vec2, Vec2)];
+
+/// A plus sign.
+const FOOD_SHAPE: Shape = &[
+    (Vec2::ZERO, Vec2::new(0.7, 0.2)),
+    (Vec2::ZERO, Vec2::new(0.2, 0.7)),
+];
+
+/// Piece of a shape drawn over food.
+#[derive(Component)]
+struct PatternPiece;
+
+/// Marks food that got its shape.
+#[derive(Component)]
+struct Patterned;
+
+fn add_patterns(
+    mut commands: Commands,
+    food_query: Query<Entity, (With<Food>, Without<Patterned>)>,
+) {
+    let food = food_query.iter().map(|entity| (entity, FOOD_SHAPE));
+
+    for (entity, shape) in food {
+        commands
+            .entity(entity)
+            .insert(Patterned)
+            .with_children(|parent| {
+                for (offset, size) in shape {
+                    parent.spawn((
+                        SpriteBundle {
+                            sprite: Sprite {
+                                custom_size: Some(*size),
+                                ..default()
+                            },
+                            // children are laid out in cell units, just above
+                            // what they cover
+                            transform: Transform::from_translation(offset.extend(0.01)),
+                            ..default()
+                        },
+                        Themed(Role::Background),
+                        PatternPiece,
+                    ));
+                }
+            });
+    }
+}
+
+fn remove_patterns(
+    mut commands: Commands,
+    patterned_query: Query<(Entity, &Children), With<Patterned>>,
+    piece_query: Query<(), With<PatternPiece>>,
+) {
+    for (entity, children) in patterned_query.iter() {
+        for child in children.iter() {
+            if piece_query.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+        commands.entity(entity).remove::<Patterned>();
+    }
+}
+
+/// Font sizes a text was spawned with, before scaling.
+#[derive(Component)]
+struct BaseFontSizes(Vec<f32>);
+
+fn scale_text(
+    mut commands: Commands,
+    accessibility: Res<Accessibility>,
+    mut new_text_query: Query<(Entity, &mut Text), Without<BaseFontSizes>>,
+    mut text_query: Query<(&mut Text, &BaseFontSizes)>,
+) {
+    let factor = accessibility.text_scale.factor();
+
+    for (entity, mut text) in new_text_query.iter_mut() {
+        let base = text
+            .sections
+            .iter()
+            .map(|section| section.style.font_size)
+            .collect::<Vec<_>>();
+        for (section, size) in text.sections.iter_mut().zip(&base) {
+            section.style.font_size = size * factor;
+        }
+        commands.entity(entity).insert(BaseFontSizes(base));
+    }
+
+    if !accessibility.is_changed() {
+        return;
+    }
+    for (mut text, base) in text_query.iter_mut() {
+        for (section, size) in text.sections.iter_mut().zip(&base.0) {
+            section.style.font_size = size * factor;
+        }
+    }
+}
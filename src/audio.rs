@@ -0,0 +1,416 @@
+//! Sound: music looping from the end of loading on, and short effects for
+//! eating, turning, dying and clicking buttons. Effects are
+//! synthesised as `Tone`s rather than loaded, so they cost no asset files.
+//! Master, music and effect volumes and a mute switch are saved.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use bevy::{
+    audio::{AddAudioSource, Decodable, Source, Volume},
+    prelude::*,
+};
+use bevy_asset_loader::asset_collection::AssetCollection;
+
+use crate::{
+    bot::Bot,
+    game::{Direction, GameOverEvent, GrowthEvent, SnakeHead},
+    storage, GameState,
+};
+
+const MASTER_KEY: &str = "settings.volume.master";
+const MUSIC_KEY: &str = "settings.volume.music";
+const SFX_KEY: &str = "settings.volume.sfx";
+const MUTED_KEY: &str = "settings.muted";
+
+const SAMPLE_RATE: u32 = 44_100;
+/// Fade in and out of each sweep, to keep it from clicking.
+const EDGE_SECONDS: f32 = 0.004;
+
+pub struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<Tone>()
+            .insert_resource(AudioSettings::load())
+            .add_event::<SfxEvent>();
+
+        app.add_systems(Startup, setup_sfx)
+            .add_systems(OnExit(GameState::Loading), start_music)
+            .add_systems(
+                Update,
+                (
+                    (eat_sfx, turn_sfx, death_sfx).run_if(in_state(GameState::Game)),
+                    click_sfx,
+                    play_sfx,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                update_music_volume.run_if(resource_changed::<AudioSettings>()),
+            );
+    }
+}
+
+#[derive(AssetCollection, Resource)]
+pub(crate) struct AudioAssets {
+    #[asset(path = "audio/flying.ogg")]
+    music: Handle<AudioSource>,
+}
+
+/// Volumes run from 0 to 1; the music and effects are scaled by the master.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub(crate) struct AudioSettings {
+    pub(crate) master: f32,
+    pub(crate) music: f32,
+    pub(crate) sfx: f32,
+    pub(crate) muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            music: 0.5,
+            sfx: 0.8,
+            muted: false,
+        }
+    }
+}
+
+/// One of the volumes set on the settings screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Channel {
+    Master,
+    Music,
+    Sfx,
+}
+
+impl Channel {
+    pub(crate) const ALL: [Self; 3] = [Self::Master, Self::Music, Self::Sfx];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Self::Master => "Master",
+            Self::Music => "Music",
+            Self::Sfx => "Effects",
+        }
+    }
+}
+
+impl AudioSettings {
+    fn load() -> Self {
+        let default = Self::default();
+        let volume = |key, default: f32| {
+            storage::load(key)
+                .and_then(|value| value.trim().parse::<f32>().ok())
+                .map_or(default, |volume| volume.clamp(0.0, 1.0))
+        };
+        Self {
+            master: volume(MASTER_KEY, default.master),
+            music: volume(MUSIC_KEY, default.music),
+            sfx: volume(SFX_KEY, default.sfx),
+            muted: storage::load(MUTED_KEY).is_some_and(|value| value.trim() == "on"),
+        }
+    }
+
+    pub(crate) fn save(&self) {
+        let saved = [
+            (MASTER_KEY, format!("{:.2}", self.master)),
+            (MUSIC_KEY, format!("{:.2}", self.music)),
+            (SFX_KEY, format!("{:.2}", self.sfx)),
+            (MUTED_KEY, if self.muted { "on" } else { "off" }.to_string()),
+        ]
+        .into_iter()
+        .try_for_each(|(key, value)| storage::save(key, &value));
+        if let Err(err) = saved {
+            warn!("Could not save the audio settings: {err}");
+        }
+    }
+
+    pub(crate) fn volume(&self, channel: Channel) -> f32 {
+        match channel {
+            Channel::Master => self.master,
+            Channel::Music => self.music,
+            Channel::Sfx => self.sfx,
+        }
+    }
+
+    pub(crate) fn set_volume(&mut self, channel: Channel, volume: f32) {
+        let volume = volume.clamp(0.0, 1.0);
+        match channel {
+            Channel::Master => self.master = volume,
+            Channel::Music => self.music = volume,
+            Channel::Sfx => self.sfx = volume,
+        }
+    }
+
+    /// Volume a channel actually plays at.
+    fn output(&self, channel: Channel) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master * self.volume(channel)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Sfx {
+    Eat,
+    Turn,
+    Death,
+    Click,
+}
+
+#[derive(Event)]
+struct SfxEvent(Sfx);
+
+#[derive(Debug, Clone, Copy)]
+enum Wave {
+    Square,
+    Triangle,
+    Noise,
+}
+
+/// Pitch gliding from one frequency to another.
+#[derive(Debug, Clone, Copy)]
+struct Sweep {
+    from: f32,
+    to: f32,
+    seconds: f32,
+}
+
+const fn sweep(from: f32, to: f32, seconds: f32) -> Sweep {
+    Sweep { from, to, seconds }
+}
+
+/// A short synthesised sound: sweeps played one after the other, each fading
+/// out over its length.
+#[derive(Asset, TypePath, Debug, Clone)]
+struct Tone {
+    wave: Wave,
+    sweeps: Vec<Sweep>,
+    /// Loudness before any volume setting.
+    gain: f32,
+}
+
+impl Sfx {
+    fn tone(&self) -> Tone {
+        let (wave, sweeps, gain) = match self {
+            Self::Eat => (Wave::Square, vec![sweep(520.0, 880.0, 0.08)], 0.25),
+            Self::Turn => (Wave::Triangle, vec![sweep(300.0, 260.0, 0.03)], 0.3),
+            Self::Death => (Wave::Noise, vec![sweep(800.0, 60.0, 0.5)], 0.35),
+            Self::Click => (Wave::Triangle, vec![sweep(900.0, 700.0, 0.025)], 0.3),
+        };
+        Tone { wave, sweeps, gain }
+    }
+}
+
+impl Decodable for Tone {
+    type DecoderItem = f32;
+    type Decoder = ToneDecoder;
+
+    fn decoder(&self) -> ToneDecoder {
+        ToneDecoder {
+            tone: self.clone(),
+            sweep: 0,
+            sample: 0,
+            phase: 0.0,
+            noise: 0x2545_f491,
+            held: 0.0,
+        }
+    }
+}
+
+struct ToneDecoder {
+    tone: Tone,
+    /// Index of the sweep playing, and of the sample within it.
+    sweep: usize,
+    sample: u32,
+    phase: f32,
+    /// State of the noise generator, and the value held until the next cycle.
+    noise: u32,
+    held: f32,
+}
+
+impl ToneDecoder {
+    fn next_noise(&mut self) -> f32 {
+        // xorshift, plenty for a crunch
+        self.noise ^= self.noise << 13;
+        self.noise ^= self.noise >> 17;
+        self.noise ^= self.noise << 5;
+        self.noise as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+impl Iterator for ToneDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sweep = *self.tone.sweeps.get(self.sweep)?;
+        let length = (sweep.seconds * SAMPLE_RATE as f32) as u32;
+        if self.sample >= length {
+            self.sweep += 1;
+            self.sample = 0;
+            return self.next();
+        }
+
+        let t = self.sample as f32 / length as f32;
+        let frequency = sweep.from + (sweep.to - sweep.from) * t;
+        let previous_phase = self.phase;
+        self.phase = (self.phase + frequency / SAMPLE_RATE as f32).fract();
+
+        let value = match self.tone.wave {
+            Wave::Square => {
+                if self.phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Wave::Triangle => 1.0 - 4.0 * (self.phase - 0.5).abs(),
+            Wave::Noise => {
+                // a new random value each cycle lowers the noise with the pitch
+                if self.phase < previous_phase {
+                    self.held = self.next_noise();
+                }
+                self.held
+            }
+        };
+        let edge = EDGE_SECONDS * SAMPLE_RATE as f32;
+        let envelope = (self.sample as f32 / edge).min(1.0) * (1.0 - t);
+        self.sample += 1;
+
+        Some(value * envelope * self.tone.gain)
+    }
+}
+
+impl Source for ToneDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        let seconds = self.tone.sweeps.iter().map(|sweep| sweep.seconds).sum();
+        Some(Duration::from_secs_f32(seconds))
+    }
+}
+
+#[derive(Resource)]
+struct SfxHandles(HashMap<Sfx, Handle<Tone>>);
+
+#[derive(Component)]
+struct Music;
+
+fn setup_sfx(mut commands: Commands, mut tones: ResMut<Assets<Tone>>) {
+    let handles = [Sfx::Eat, Sfx::Turn, Sfx::Death, Sfx::Click]
+        .into_iter()
+        .map(|sfx| (sfx, tones.add(sfx.tone())))
+        .collect();
+    commands.insert_resource(SfxHandles(handles));
+}
+
+fn start_music(
+    mut commands: Commands,
+    audio_assets: Res<AudioAssets>,
+    settings: Res<AudioSettings>,
+) {
+    commands.spawn((
+        AudioBundle {
+            source: audio_assets.music.clone(),
+            settings: PlaybackSettings::LOOP
+                .with_volume(Volume::new_absolute(settings.output(Channel::Music))),
+        },
+        Music,
+    ));
+}
+
+fn update_music_volume(settings: Res<AudioSettings>, music_query: Query<&AudioSink, With<Music>>) {
+    for sink in music_query.iter() {
+        sink.set_volume(settings.output(Channel::Music));
+    }
+}
+
+fn eat_sfx(mut growth_ev_reader: EventReader<GrowthEvent>, mut sfx: EventWriter<SfxEvent>) {
+    if growth_ev_reader.read().count() > 0 {
+        sfx.send(SfxEvent(Sfx::Eat));
+    }
+}
+
+/// Ticks whenever a player's snake changes direction; bots stay quiet.
+fn turn_sfx(
+    mut last: Local<HashMap<Entity, Direction>>,
+    head_query: Query<(Entity, &SnakeHead), Without<Bot>>,
+    mut sfx: EventWriter<SfxEvent>,
+) {
+    let mut seen = HashSet::new();
+    let mut turned = false;
+
+    for (entity, head) in head_query.iter() {
+        seen.insert(entity);
+        let previous = last.insert(entity, head.direction());
+        turned |= previous.is_some_and(|previous| previous != head.direction());
+    }
+    last.retain(|entity, _| seen.contains(entity));
+
+    if turned {
+        sfx.send(SfxEvent(Sfx::Turn));
+    }
+}
+
+fn death_sfx(mut game_over_ev_reader: EventReader<GameOverEvent>, mut sfx: EventWriter<SfxEvent>) {
+    if game_over_ev_reader.read().count() > 0 {
+        sfx.send(SfxEvent(Sfx::Death));
+    }
+}
+
+fn click_sfx(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    mut sfx: EventWriter<SfxEvent>,
+) {
+    if interaction_query
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        sfx.send(SfxEvent(Sfx::Click));
+    }
+}
+
+/// Plays each effect at most once a frame, however many asked for it.
+fn play_sfx(
+    mut commands: Commands,
+    settings: Res<AudioSettings>,
+    handles: Res<SfxHandles>,
+    mut sfx_ev_reader: EventReader<SfxEvent>,
+) {
+    let volume = settings.output(Channel::Sfx);
+    let requested = sfx_ev_reader
+        .read()
+        .map(|SfxEvent(sfx)| *sfx)
+        .collect::<HashSet<_>>();
+    if volume <= 0.0 {
+        return;
+    }
+
+    for sfx in requested {
+        let Some(handle) = handles.0.get(&sfx) else {
+            continue;
+        };
+        commands.spawn(AudioSourceBundle {
+            source: handle.clone(),
+            settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_absolute(volume)),
+        });
+    }
+}
@@ -0,0 +1,114 @@
+//! Computer-controlled snakes. A bot takes the turn that leaves the most room
+//! around its head and, among turns roomy enough, the one closest to food.
+
+use std::{
+    cmp::Reverse,
+    collections::{HashSet, VecDeque},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{Direction, Food, Position, SnakeBody, SnakeHead, SnakeStep},
+    grid_movement,
+    level::Arena,
+    RoundState,
+};
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Left,
+    Direction::Up,
+    Direction::Right,
+    Direction::Down,
+];
+/// Free cells per body segment a bot wants ahead of it before it goes after food.
+const ROOM_PER_SEGMENT: usize = 2;
+const MIN_ROOM: usize = 12;
+
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            steer_bots
+                .before(SnakeStep)
+                .run_if(in_state(RoundState::Playing))
+                .run_if(grid_movement),
+        );
+    }
+}
+
+/// Snake steered by the computer rather than the keyboard.
+#[derive(Component)]
+pub(crate) struct Bot;
+
+fn steer_bots(
+    arena: Res<Arena>,
+    mut bot_query: Query<(&mut SnakeHead, &SnakeBody), With<Bot>>,
+    body_query: Query<&SnakeBody>,
+    positions_query: Query<&Position>,
+    food_query: Query<&Position, With<Food>>,
+) {
+    let occupied = body_query
+        .iter()
+        .flat_map(|body| body.iter())
+        .filter_map(|segment| positions_query.get(*segment).ok().copied())
+        .collect::<HashSet<Position>>();
+    let food = food_query.iter().copied().collect::<Vec<Position>>();
+
+    for (mut head, body) in bot_query.iter_mut() {
+        let Some(head_pos) = body
+            .first()
+            .and_then(|head| positions_query.get(*head).ok())
+        else {
+            continue;
+        };
+        let enough_room = (body.len() * ROOM_PER_SEGMENT).max(MIN_ROOM);
+
+        let best = DIRECTIONS
+            .into_iter()
+            .filter(|direction| *direction != head.direction().opposite())
+            .map(|direction| {
+                let next = arena.step(head_pos, direction);
+                let room = if arena.is_deadly(&next) || occupied.contains(&next) {
+                    0
+                } else {
+                    room(&arena, &occupied, next, enough_room)
+                };
+                let distance = food
+                    .iter()
+                    .map(|food| (food.x - next.x).abs() + (food.y - next.y).abs())
+                    .min()
+                    .unwrap_or_default();
+                (direction, room, distance)
+            })
+            .max_by_key(|(direction, room, distance)| {
+                (*room, Reverse(*distance), *direction == head.direction())
+            });
+
+        if let Some((direction, _, _)) = best {
+            head.steer(direction);
+        }
+    }
+}
+
+/// Free cells reachable from `start`, counting no further than `limit`.
+fn room(arena: &Arena, occupied: &HashSet<Position>, start: Position, limit: usize) -> usize {
+    let mut reached = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(cell) = queue.pop_front() {
+        if reached.len() >= limit {
+            break;
+        }
+        for direction in DIRECTIONS {
+            let next = arena.step(&cell, direction);
+            if !arena.is_deadly(&next) && !occupied.contains(&next) && reached.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    reached.len().min(limit)
+}
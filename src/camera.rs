@@ -0,0 +1,273 @@
+//! Cameras: the main one follows the player across arenas too big for the
+//! window, while a minimap in the corner shows the whole arena. Arenas that
+//! fit are framed by letterbox bars wherever the window is left uncovered.
+
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig, prelude::*, render::camera::Viewport,
+    transform::TransformSystem, window::PrimaryWindow,
+};
+
+use crate::{
+    death::CameraShake,
+    game::{arena_bounds, arena_to_world, is_scrolling, Player, Position, SnakeHead},
+    hex::hex_round,
+    hud::HUD_HEIGHT,
+    level::{Arena, CurrentLevel},
+    theme::{Palette, Role, Themed},
+    GameMode, GameState,
+};
+
+/// How quickly the camera catches up with the head, per second.
+const FOLLOW_RATE: f32 = 6.0;
+/// Longest side of the minimap, in logical pixels.
+const MINIMAP_SIZE: f32 = 150.0;
+const MINIMAP_MARGIN: f32 = 10.0;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CameraFollow::default());
+
+        app.add_systems(Startup, setup)
+            .add_systems(OnExit(GameState::Game), reset_follow)
+            .add_systems(
+                PostUpdate,
+                (
+                    follow_player.run_if(in_state(GameState::Game)),
+                    apply_camera,
+                    update_minimap,
+                    update_letterbox,
+                )
+                    .chain()
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Camera the game is played through.
+#[derive(Component)]
+pub(crate) struct MainCamera;
+
+#[derive(Component)]
+struct MinimapCamera;
+
+/// Covers one side of the window outside the arena. Bars are children of the
+/// main camera so they stay put while it moves.
+#[derive(Component, Clone, Copy)]
+enum LetterboxBar {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// Where the main camera looks, before any shake. Unset until the first
+/// frame of a round so the camera starts on the snake.
+#[derive(Resource, Default)]
+struct CameraFollow(Option<Vec2>);
+
+fn setup(mut commands: Commands) {
+    commands
+        .spawn((Camera2dBundle::default(), MainCamera))
+        .with_children(|parent| {
+            for bar in [
+                LetterboxBar::Left,
+                LetterboxBar::Right,
+                LetterboxBar::Top,
+                LetterboxBar::Bottom,
+            ] {
+                parent.spawn((
+                    SpriteBundle {
+                        // in front of the arena, relative to the camera
+                        transform: Transform::from_xyz(0.0, 0.0, -1.0),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    Themed(Role::Letterbox),
+                    bar,
+                ));
+            }
+        });
+
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                // drawn over the main camera
+                order: 1,
+                is_active: false,
+                ..default()
+            },
+            ..default()
+        },
+        UiCameraConfig { show_ui: false },
+        MinimapCamera,
+    ));
+}
+
+fn reset_follow(mut follow: ResMut<CameraFollow>) {
+    follow.0 = None;
+}
+
+fn follow_player(
+    time: Res<Time>,
+    arena: Res<Arena>,
+    mut follow: ResMut<CameraFollow>,
+    primary_query: Query<&Window, With<PrimaryWindow>>,
+    head_query: Query<(&Player, &Position), With<SnakeHead>>,
+) {
+    let Ok(window) = primary_query.get_single() else {
+        return;
+    };
+    if !is_scrolling(window, &arena) {
+        follow.0 = Some(Vec2::ZERO);
+        return;
+    }
+    let Some((_, head_pos)) = head_query.iter().find(|(player, _)| player.0 == 0) else {
+        return;
+    };
+
+    let head = arena_to_world(
+        Vec2::new(head_pos.x as f32, head_pos.y as f32),
+        window,
+        &arena,
+    );
+    let target = clamp_to_arena(head, window, &arena);
+    let current = follow.0.unwrap_or(target);
+    let t = 1.0 - (-FOLLOW_RATE * time.delta_seconds()).exp();
+    follow.0 = Some(current.lerp(target, t));
+}
+
+/// Keeps the view inside the arena, centring it on axes where the arena is
+/// smaller than the window.
+fn clamp_to_arena(target: Vec2, window: &Window, arena: &Arena) -> Vec2 {
+    let bounds = arena_bounds(window, arena);
+    let half_view = Vec2::new(window.width(), window.height()) / 2.0;
+    // the top of the view is covered by the HUD bar
+    let low = bounds.min + half_view;
+    let high = bounds.max - half_view + Vec2::new(0.0, HUD_HEIGHT);
+
+    let clamp = |value: f32, low: f32, high: f32| {
+        if low > high {
+            (low + high) / 2.0
+        } else {
+            value.clamp(low, high)
+        }
+    };
+    Vec2::new(
+        clamp(target.x, low.x, high.x),
+        clamp(target.y, low.y, high.y),
+    )
+}
+
+fn apply_camera(
+    follow: Res<CameraFollow>,
+    shake: Res<CameraShake>,
+    mut camera_query: Query<&mut Transform, With<MainCamera>>,
+) {
+    let offset = follow.0.unwrap_or_default() + shake.offset;
+    for mut transform in camera_query.iter_mut() {
+        transform.translation.x = offset.x;
+        transform.translation.y = offset.y;
+    }
+}
+
+fn update_minimap(
+    state: Res<State<GameState>>,
+    arena: Option<Res<Arena>>,
+    palette: Res<Palette>,
+    primary_query: Query<&Window, With<PrimaryWindow>>,
+    mut minimap_query: Query<
+        (
+            &mut Camera,
+            &mut Camera2d,
+            &mut OrthographicProjection,
+            &mut Transform,
+        ),
+        With<MinimapCamera>,
+    >,
+) {
+    let Ok((mut camera, mut camera_2d, mut projection, mut transform)) =
+        minimap_query.get_single_mut()
+    else {
+        return;
+    };
+    if palette.is_changed() {
+        camera_2d.clear_color = ClearColorConfig::Custom(palette.color(Role::Panel));
+    }
+    let (Ok(window), Some(arena)) = (primary_query.get_single(), arena) else {
+        camera.is_active = false;
+        return;
+    };
+
+    let active = *state.get() == GameState::Game && is_scrolling(window, &arena);
+    if camera.is_active != active {
+        camera.is_active = active;
+    }
+    if !active {
+        return;
+    }
+
+    let bounds = arena_bounds(window, &arena);
+    let size = bounds.size() * (MINIMAP_SIZE / bounds.size().max_element());
+    let scale_factor = window.scale_factor() as f32;
+    let position = Vec2::new(
+        window.width() - size.x - MINIMAP_MARGIN,
+        window.height() - size.y - MINIMAP_MARGIN,
+    );
+    camera.viewport = Some(Viewport {
+        physical_position: (position * scale_factor).as_uvec2(),
+        physical_size: (size * scale_factor).as_uvec2().max(UVec2::ONE),
+        ..default()
+    });
+    projection.scale = (bounds.size() / size).max_element();
+    transform.translation = bounds.center().extend(transform.translation.z);
+}
+
+fn update_letterbox(
+    state: Res<State<GameState>>,
+    arena: Option<Res<Arena>>,
+    follow: Res<CameraFollow>,
+    mode: Res<GameMode>,
+    current_level: Res<CurrentLevel>,
+    primary_query: Query<&Window, With<PrimaryWindow>>,
+    mut bar_query: Query<(&LetterboxBar, &mut Transform, &mut Visibility)>,
+) {
+    let Ok(window) = primary_query.get_single() else {
+        return;
+    };
+    // the hex board sizes itself and ignores the arena
+    let framed = matches!(state.get(), GameState::Game | GameState::Editor)
+        && !hex_round(mode, current_level);
+    let arena = arena.filter(|arena| framed && !is_scrolling(window, arena));
+    let Some(arena) = arena else {
+        for (_, _, mut visibility) in bar_query.iter_mut() {
+            *visibility = Visibility::Hidden;
+        }
+        return;
+    };
+
+    // both rectangles relative to the camera, shake aside
+    let view = Rect::from_center_size(Vec2::ZERO, Vec2::new(window.width(), window.height()));
+    let bounds = arena_bounds(window, &arena);
+    let inner = Rect::from_corners(
+        bounds.min - follow.0.unwrap_or_default(),
+        bounds.max - follow.0.unwrap_or_default(),
+    );
+
+    for (bar, mut transform, mut visibility) in bar_query.iter_mut() {
+        let rect = match bar {
+            LetterboxBar::Left => Rect::new(view.min.x, view.min.y, inner.min.x, view.max.y),
+            LetterboxBar::Right => Rect::new(inner.max.x, view.min.y, view.max.x, view.max.y),
+            LetterboxBar::Top => Rect::new(inner.min.x, inner.max.y, inner.max.x, view.max.y),
+            LetterboxBar::Bottom => Rect::new(inner.min.x, view.min.y, inner.max.x, inner.min.y),
+        };
+        *visibility = if rect.is_empty() {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        transform.translation = rect.center().extend(transform.translation.z);
+        transform.scale = rect.size().extend(1.0);
+    }
+}
@@ -0,0 +1,539 @@
+//! Campaign: a fixed list of levels, each with a goal to reach, unlocked one
+//! after the other. Completed levels earn up to three stars against their
+//! par, and progress is saved.
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{GrowthEvent, Player, RoundSetup, RoundTime, SnakeBody},
+    level::{CurrentLevel, Goal, Level, LevelAssets},
+    storage,
+    theme::{Palette, Role, Themed},
+    GameState, RoundState,
+};
+
+const PROGRESS_KEY: &str = "campaign.progress";
+const MAX_STARS: u8 = 3;
+
+const LOCKED_ROLE: Role = Role::ButtonDisabled;
+const UNLOCKED_ROLE: Role = Role::ButtonPrimary;
+const COMPLETED_ROLE: Role = Role::ButtonTertiary;
+const BACK_ROLE: Role = Role::ButtonDanger;
+
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CampaignProgress::load())
+            .insert_resource(CampaignRun(None))
+            .insert_resource(FoodEaten(0))
+            .insert_resource(LevelResult::default());
+
+        // endless play and the editor never run campaign goals
+        app.add_systems(OnEnter(GameState::Menu), leave_campaign);
+
+        app.add_systems(OnEnter(GameState::LevelSelect), setup_level_select)
+            .add_systems(
+                Update,
+                (select_level, back_to_menu).run_if(in_state(GameState::LevelSelect)),
+            )
+            .add_systems(OnExit(GameState::LevelSelect), cleanup_campaign_ui);
+
+        app.add_systems(
+            OnEnter(GameState::Game),
+            reset_food_eaten.in_set(RoundSetup::Reset),
+        )
+        .add_systems(
+            Update,
+            (count_food, check_goal)
+                .chain()
+                .run_if(in_state(RoundState::Playing))
+                .run_if(in_campaign),
+        );
+
+        app.add_systems(OnEnter(GameState::LevelComplete), setup_level_complete)
+            .add_systems(
+                Update,
+                handle_level_complete_buttons.run_if(in_state(GameState::LevelComplete)),
+            )
+            .add_systems(OnExit(GameState::LevelComplete), cleanup_campaign_ui);
+    }
+}
+
+/// Best star rating per campaign level, 0 when not completed yet.
+#[derive(Resource, Default)]
+pub(crate) struct CampaignProgress {
+    stars: Vec<u8>,
+}
+
+impl CampaignProgress {
+    fn load() -> Self {
+        let stars = storage::load(PROGRESS_KEY)
+            .map(|source| {
+                source
+                    .lines()
+                    .map(|line| line.trim().parse().unwrap_or(0).min(MAX_STARS))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { stars }
+    }
+
+    fn save(&self) {
+        let source = self
+            .stars
+            .iter()
+            .map(|stars| stars.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if let Err(err) = storage::save(PROGRESS_KEY, &source) {
+            warn!("Could not save campaign progress: {err}");
+        }
+    }
+
+    /// Number of levels completed at least once.
+    pub(crate) fn completed(&self) -> usize {
+        self.stars.iter().filter(|stars| **stars > 0).count()
+    }
+
+    pub(crate) fn total_stars(&self) -> usize {
+        self.stars.iter().map(|stars| *stars as usize).sum()
+    }
+
+    fn stars(&self, index: usize) -> u8 {
+        self.stars.get(index).copied().unwrap_or(0)
+    }
+
+    /// The first level is always open, the others once the previous one is done.
+    fn is_unlocked(&self, index: usize) -> bool {
+        index == 0 || self.stars(index - 1) > 0
+    }
+
+    /// Keeps the best rating, returns whether it improved.
+    fn record(&mut self, index: usize, stars: u8) -> bool {
+        if self.stars.len() <= index {
+            self.stars.resize(index + 1, 0);
+        }
+        if stars <= self.stars[index] {
+            return false;
+        }
+        self.stars[index] = stars;
+        self.save();
+        true
+    }
+}
+
+/// Index of the campaign level being played, `None` in endless play.
+#[derive(Resource, Debug, PartialEq, Eq)]
+pub(crate) struct CampaignRun(pub(crate) Option<usize>);
+
+#[derive(Resource)]
+struct FoodEaten(usize);
+
+/// Outcome of the last completed level, shown on the level complete screen.
+#[derive(Resource, Default)]
+struct LevelResult {
+    stars: u8,
+    new_best: bool,
+}
+
+#[derive(Component)]
+struct CampaignUI;
+
+#[derive(Component)]
+struct LevelButton(usize);
+
+#[derive(Component)]
+struct BackButton;
+
+#[derive(Component)]
+enum LevelCompleteButton {
+    Next,
+    Retry,
+    Levels,
+}
+
+fn in_campaign(run: Res<CampaignRun>) -> bool {
+    run.0.is_some()
+}
+
+fn leave_campaign(mut run: ResMut<CampaignRun>, mut current_level: ResMut<CurrentLevel>) {
+    run.0 = None;
+    current_level.0 = None;
+}
+
+fn goal_label(level: Option<&Level>) -> String {
+    level
+        .and_then(|level| level.rules.goal)
+        .map(|goal| goal.label())
+        .unwrap_or_default()
+}
+
+fn stars_label(stars: u8) -> String {
+    (0..MAX_STARS)
+        .map(|star| if star < stars { "*" } else { "-" })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn button(role: Role, width: f32) -> (ButtonBundle, Themed) {
+    (
+        ButtonBundle {
+            style: Style {
+                width: Val::Px(width),
+                height: Val::Px(40.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        },
+        Themed(role),
+    )
+}
+
+fn setup_level_select(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    level_assets: Res<LevelAssets>,
+    levels: Res<Assets<Level>>,
+    progress: Res<CampaignProgress>,
+    palette: Res<Palette>,
+) {
+    let font = asset_server.load("fonts/KnightWarrior.otf");
+    let text = |value: String, font_size: f32, role: Role| {
+        (
+            TextBundle::from_section(
+                value,
+                TextStyle {
+                    font: font.clone(),
+                    font_size,
+                    ..default()
+                },
+            ),
+            Themed(role),
+        )
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.0),
+                    ..default()
+                },
+                ..default()
+            },
+            Themed(Role::Background),
+            CampaignUI,
+        ))
+        .with_children(|children| {
+            children.spawn(text("Campaign".to_string(), 48.0, Role::Text));
+
+            for (index, handle) in level_assets.campaign.iter().enumerate() {
+                let level = levels.get(handle);
+                let name = level.map(|level| level.name.as_str()).unwrap_or("?");
+                let stars = progress.stars(index);
+
+                // locked levels are plain nodes so they cannot be clicked
+                let mut row = if !progress.is_unlocked(index) {
+                    children.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Px(420.0),
+                                height: Val::Px(40.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        Themed(LOCKED_ROLE),
+                    ))
+                } else if stars > 0 {
+                    children.spawn((button(COMPLETED_ROLE, 420.0), LevelButton(index)))
+                } else {
+                    children.spawn((button(UNLOCKED_ROLE, 420.0), LevelButton(index)))
+                };
+
+                row.with_children(|parent| {
+                    if progress.is_unlocked(index) {
+                        parent.spawn(TextBundle::from_sections([
+                            TextSection::new(
+                                format!("{}. {}  ", index + 1, name),
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: 20.0,
+                                    color: palette.color(Role::Text),
+                                },
+                            ),
+                            TextSection::new(
+                                format!("{}  ", goal_label(level)),
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: 14.0,
+                                    color: palette.color(Role::TextMuted),
+                                },
+                            ),
+                            TextSection::new(
+                                stars_label(stars),
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: 20.0,
+                                    color: palette.color(Role::TextAccent),
+                                },
+                            ),
+                        ]));
+                    } else {
+                        parent.spawn(text(
+                            format!("{}. Locked", index + 1),
+                            20.0,
+                            Role::TextMuted,
+                        ));
+                    }
+                });
+            }
+
+            children
+                .spawn((button(BACK_ROLE, 160.0), BackButton))
+                .with_children(|parent| {
+                    parent.spawn(text("Back".to_string(), 24.0, Role::Text));
+                });
+        });
+}
+
+fn select_level(
+    interaction_query: Query<(&Interaction, &LevelButton), Changed<Interaction>>,
+    level_assets: Res<LevelAssets>,
+    mut run: ResMut<CampaignRun>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction == Interaction::Pressed {
+            run.0 = Some(button.0);
+            current_level.0 = level_assets.campaign.get(button.0).cloned();
+            next_state.set(GameState::Game);
+        }
+    }
+}
+
+fn back_to_menu(
+    keyboard_input: Res<Input<KeyCode>>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape)
+        || interaction_query
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        next_state.set(GameState::Menu);
+    }
+}
+
+fn reset_food_eaten(mut food_eaten: ResMut<FoodEaten>) {
+    food_eaten.0 = 0;
+}
+
+fn count_food(mut food_eaten: ResMut<FoodEaten>, mut growth_ev_reader: EventReader<GrowthEvent>) {
+    food_eaten.0 += growth_ev_reader.read().count();
+}
+
+/// Length and eat goals are rated by time against par, survive goals by food
+/// eaten against par.
+fn rate(goal: Goal, par: u32, round_time: &RoundTime, food_eaten: usize) -> u8 {
+    let par = par as f32;
+    match goal {
+        Goal::Length(_) | Goal::Eat(_) => {
+            let secs = round_time.elapsed().as_secs_f32();
+            if secs <= par {
+                3
+            } else if secs <= par * 1.5 {
+                2
+            } else {
+                1
+            }
+        }
+        Goal::Survive(_) => {
+            let food = food_eaten as f32;
+            if food >= par {
+                3
+            } else if food >= par / 2.0 {
+                2
+            } else {
+                1
+            }
+        }
+    }
+}
+
+fn check_goal(
+    run: Res<CampaignRun>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+    body_query: Query<(&Player, &SnakeBody)>,
+    food_eaten: Res<FoodEaten>,
+    round_time: Res<RoundTime>,
+    mut progress: ResMut<CampaignProgress>,
+    mut result: ResMut<LevelResult>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let (Some(index), Some(level)) = (
+        run.0,
+        current_level
+            .0
+            .as_ref()
+            .and_then(|handle| levels.get(handle)),
+    ) else {
+        return;
+    };
+    let Some(goal) = level.rules.goal else {
+        return;
+    };
+
+    let reached = match goal {
+        Goal::Length(length) => body_query
+            .iter()
+            .any(|(player, body)| player.0 == 0 && body.len() >= length),
+        Goal::Eat(food) => food_eaten.0 >= food,
+        Goal::Survive(time) => round_time.elapsed() >= time,
+    };
+    if !reached {
+        return;
+    }
+
+    let stars = rate(
+        goal,
+        level.rules.par.unwrap_or_default(),
+        &round_time,
+        food_eaten.0,
+    );
+    *result = LevelResult {
+        stars,
+        new_best: progress.record(index, stars),
+    };
+    next_state.set(GameState::LevelComplete);
+}
+
+fn setup_level_complete(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    run: Res<CampaignRun>,
+    level_assets: Res<LevelAssets>,
+    levels: Res<Assets<Level>>,
+    result: Res<LevelResult>,
+) {
+    let font = asset_server.load("fonts/KnightWarrior.otf");
+    let text = |value: String, font_size: f32, role: Role| {
+        (
+            TextBundle::from_section(
+                value,
+                TextStyle {
+                    font: font.clone(),
+                    font_size,
+                    ..default()
+                },
+            ),
+            Themed(role),
+        )
+    };
+    let name = run
+        .0
+        .and_then(|index| level_assets.campaign.get(index))
+        .and_then(|handle| levels.get(handle))
+        .map(|level| level.name.clone())
+        .unwrap_or_default();
+    let has_next = run
+        .0
+        .is_some_and(|index| index + 1 < level_assets.campaign.len());
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                ..default()
+            },
+            Themed(Role::Background),
+            CampaignUI,
+        ))
+        .with_children(|children| {
+            children.spawn(text("Level complete".to_string(), 48.0, Role::Text));
+            children.spawn(text(name, 28.0, Role::TextMuted));
+            children.spawn(text(stars_label(result.stars), 48.0, Role::TextAccent));
+            if result.new_best {
+                children.spawn(text("New best!".to_string(), 20.0, Role::TextAccent));
+            }
+
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(20.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|children| {
+                    let mut buttons = vec![
+                        (LevelCompleteButton::Retry, "Retry", UNLOCKED_ROLE),
+                        (LevelCompleteButton::Levels, "Levels", BACK_ROLE),
+                    ];
+                    if has_next {
+                        buttons.insert(0, (LevelCompleteButton::Next, "Next", COMPLETED_ROLE));
+                    }
+
+                    for (action, label, role) in buttons {
+                        children
+                            .spawn((button(role, 140.0), action))
+                            .with_children(|parent| {
+                                parent.spawn(text(label.to_string(), 24.0, Role::Text));
+                            });
+                    }
+                });
+        });
+}
+
+fn handle_level_complete_buttons(
+    interaction_query: Query<(&Interaction, &LevelCompleteButton), Changed<Interaction>>,
+    level_assets: Res<LevelAssets>,
+    mut run: ResMut<CampaignRun>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, action) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match action {
+            LevelCompleteButton::Next => {
+                let next = run.0.map_or(0, |index| index + 1);
+                run.0 = Some(next);
+                current_level.0 = level_assets.campaign.get(next).cloned();
+                next_state.set(GameState::Game);
+            }
+            LevelCompleteButton::Retry => next_state.set(GameState::Game),
+            LevelCompleteButton::Levels => next_state.set(GameState::LevelSelect),
+        }
+    }
+}
+
+fn cleanup_campaign_ui(mut commands: Commands, query: Query<Entity, With<CampaignUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
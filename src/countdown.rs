@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    game::RoundCleanup,
+    theme::{Role, Themed},
+    RoundState,
+};
+
+const COUNTDOWN_SECONDS: u64 = 3;
+const GO_DISPLAY_TIME: Duration = Duration::from_millis(600);
+
+pub struct CountdownPlugin;
+
+impl Plugin for CountdownPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CountdownTimer::default())
+            .insert_resource(GoTimer::default());
+
+        app.add_systems(OnEnter(RoundState::Countdown), setup_countdown)
+            .add_systems(
+                Update,
+                update_countdown.run_if(in_state(RoundState::Countdown)),
+            )
+            .add_systems(OnEnter(RoundState::Playing), show_go)
+            .add_systems(Update, hide_go.run_if(in_state(RoundState::Playing)));
+    }
+}
+
+#[derive(Component)]
+struct CountdownOverlay;
+
+#[derive(Resource, Deref, DerefMut)]
+struct CountdownTimer(Timer);
+
+impl Default for CountdownTimer {
+    fn default() -> Self {
+        Self(Timer::new(
+            Duration::from_secs(COUNTDOWN_SECONDS),
+            TimerMode::Once,
+        ))
+    }
+}
+
+#[derive(Resource, Deref, DerefMut)]
+struct GoTimer(Timer);
+
+impl Default for GoTimer {
+    fn default() -> Self {
+        Self(Timer::new(GO_DISPLAY_TIME, TimerMode::Once))
+    }
+}
+
+fn setup_countdown(
+    mut commands: Commands,
+    mut timer: ResMut<CountdownTimer>,
+    asset_server: Res<AssetServer>,
+) {
+    *timer = CountdownTimer::default();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            CountdownOverlay,
+            RoundCleanup,
+        ))
+        .with_children(|children| {
+            children.spawn((
+                TextBundle::from_section(
+                    COUNTDOWN_SECONDS.to_string(),
+                    TextStyle {
+                        font: asset_server.load("fonts/KnightWarrior.otf"),
+                        font_size: 96.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+                Themed(Role::Text),
+            ));
+        });
+}
+
+fn update_countdown(
+    time: Res<Time>,
+    mut timer: ResMut<CountdownTimer>,
+    mut next_round_state: ResMut<NextState<RoundState>>,
+    overlay_query: Query<&Children, With<CountdownOverlay>>,
+    mut text_query: Query<&mut Text>,
+) {
+    if timer.tick(time.delta()).finished() {
+        next_round_state.set(RoundState::Playing);
+        return;
+    }
+
+    let remaining = timer.remaining().as_secs_f32().ceil().to_string();
+    for children in overlay_query.iter() {
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                if text.sections[0].value != remaining {
+                    text.sections[0].value = remaining.clone();
+                }
+            }
+        }
+    }
+}
+
+fn show_go(
+    mut go_timer: ResMut<GoTimer>,
+    overlay_query: Query<&Children, With<CountdownOverlay>>,
+    mut text_query: Query<&mut Text>,
+) {
+    *go_timer = GoTimer::default();
+
+    for children in overlay_query.iter() {
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = "Go!".to_string();
+            }
+        }
+    }
+}
+
+fn hide_go(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut go_timer: ResMut<GoTimer>,
+    overlay_query: Query<Entity, With<CountdownOverlay>>,
+) {
+    if go_timer.tick(time.delta()).just_finished() {
+        for entity in overlay_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use enum_iterator::Sequence;
+use rand::random;
+
+use crate::{
+    accessibility::{full_motion, Accessibility},
+    game::{Dead, SnakeBody},
+    storage, GameState, RoundState,
+};
+
+const DEATH_STYLE_KEY: &str = "settings.death_style";
+
+const FLASH_INTERVAL: Duration = Duration::from_millis(100);
+const POP_INTERVAL: Duration = Duration::from_millis(120);
+const LINGER_TIME: Duration = Duration::from_millis(500);
+const SHAKE_TIME: Duration = Duration::from_millis(300);
+const SHAKE_INTENSITY: f32 = 8.0;
+
+pub struct DeathPlugin;
+
+impl Plugin for DeathPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DeathStyle::load())
+            .insert_resource(DeathAnimation::default())
+            .insert_resource(CameraShake::default());
+
+        app.add_systems(OnEnter(RoundState::Dying), start_death_animation)
+            .add_systems(
+                Update,
+                (
+                    (
+                        flash_head.run_if(full_motion),
+                        (pop_segments, finish_death_animation).chain(),
+                    )
+                        .run_if(resource_equals(DeathStyle::Pop)),
+                    skip_death_animation,
+                )
+                    .run_if(in_state(RoundState::Dying)),
+            )
+            .add_systems(Update, shake_camera);
+    }
+}
+
+/// How the snake falls apart once it dies, picked on the settings screen.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq, Sequence)]
+pub(crate) enum DeathStyle {
+    /// Segments pop off one by one from tail to head.
+    Pop,
+    /// Segments turn into rigid bodies and collapse inside the arena.
+    #[default]
+    Ragdoll,
+}
+
+impl DeathStyle {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Self::Pop => "Pop",
+            Self::Ragdoll => "Collapse",
+        }
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Pop => "pop",
+            Self::Ragdoll => "ragdoll",
+        }
+    }
+
+    fn load() -> Self {
+        storage::load(DEATH_STYLE_KEY)
+            .and_then(|value| {
+                enum_iterator::all::<Self>().find(|style| style.key() == value.trim())
+            })
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self) {
+        if let Err(err) = storage::save(DEATH_STYLE_KEY, self.key()) {
+            warn!("Could not save the death animation: {err}");
+        }
+    }
+}
+
+#[derive(Resource)]
+struct DeathAnimation {
+    flash: Timer,
+    pop: Timer,
+    linger: Timer,
+}
+
+impl Default for DeathAnimation {
+    fn default() -> Self {
+        Self {
+            flash: Timer::new(FLASH_INTERVAL, TimerMode::Repeating),
+            pop: Timer::new(POP_INTERVAL, TimerMode::Repeating),
+            linger: Timer::new(LINGER_TIME, TimerMode::Once),
+        }
+    }
+}
+
+/// Jitters the camera around where it would be until the timer runs out.
+#[derive(Resource)]
+pub(crate) struct CameraShake {
+    timer: Timer,
+    intensity: f32,
+    /// Added to the camera's position.
+    pub(crate) offset: Vec2,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        let mut timer = Timer::new(SHAKE_TIME, TimerMode::Once);
+        timer.tick(SHAKE_TIME);
+        Self {
+            timer,
+            intensity: SHAKE_INTENSITY,
+            offset: Vec2::ZERO,
+        }
+    }
+}
+
+impl CameraShake {
+    pub(crate) fn start(&mut self) {
+        self.timer.reset();
+    }
+}
+
+fn start_death_animation(mut animation: ResMut<DeathAnimation>, mut shake: ResMut<CameraShake>) {
+    *animation = DeathAnimation::default();
+    shake.start();
+}
+
+fn flash_head(
+    time: Res<Time>,
+    mut animation: ResMut<DeathAnimation>,
+    mut head_query: Query<&mut Visibility, With<Dead>>,
+) {
+    if animation.flash.tick(time.delta()).just_finished() {
+        for mut visibility in head_query.iter_mut() {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Visible,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+fn pop_segments(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut animation: ResMut<DeathAnimation>,
+    mut body_query: Query<&mut SnakeBody, With<Dead>>,
+) {
+    if animation.pop.tick(time.delta()).just_finished() {
+        for mut body in body_query.iter_mut() {
+            if let Some(segment) = body.pop() {
+                commands.entity(segment).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn finish_death_animation(
+    time: Res<Time>,
+    body_query: Query<&SnakeBody, With<Dead>>,
+    mut animation: ResMut<DeathAnimation>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if body_query.iter().all(|body| body.is_empty())
+        && animation.linger.tick(time.delta()).just_finished()
+    {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn skip_death_animation(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.get_just_pressed().next().is_some() {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn shake_camera(
+    time: Res<Time>,
+    accessibility: Res<Accessibility>,
+    mut shake: ResMut<CameraShake>,
+) {
+    if shake.timer.finished() {
+        return;
+    }
+    if accessibility.reduced_motion {
+        let duration = shake.timer.duration();
+        shake.timer.tick(duration);
+        shake.offset = Vec2::ZERO;
+        return;
+    }
+
+    shake.timer.tick(time.delta());
+    let strength = shake.intensity * shake.timer.percent_left();
+
+    shake.offset = if shake.timer.finished() {
+        Vec2::ZERO
+    } else {
+        Vec2::new(random::<f32>() * 2.0 - 1.0, random::<f32>() * 2.0 - 1.0) * strength
+    };
+}
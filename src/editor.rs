@@ -0,0 +1,549 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    camera::MainCamera,
+    game::{world_to_arena, Direction, Position, Size},
+    hud::HUD_HEIGHT,
+    level::{Arena, CurrentLevel, FoodPlacement, Level, MAX_PORTAL_PAIRS, PORTAL_COLORS},
+    storage,
+    theme::{Palette, Role, Themed},
+    GameState,
+};
+
+const MIN_ARENA_SIZE: u32 = 5;
+const MAX_ARENA_SIZE: u32 = 40;
+
+/// Storage key the edited level is saved under.
+const LEVEL_KEY: &str = "custom.level";
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Editor::default())
+            .insert_resource(TestPlay(false));
+
+        app.add_systems(OnEnter(GameState::Editor), setup_editor)
+            .add_systems(
+                Update,
+                (
+                    select_tool,
+                    resize_arena,
+                    paint,
+                    save_or_load,
+                    start_test_play,
+                    leave_editor,
+                    (sync_arena, draw_tiles, update_toolbar).run_if(
+                        resource_changed::<Editor>().or_else(resource_changed::<Palette>()),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Editor)),
+            )
+            .add_systems(OnExit(GameState::Editor), cleanup_editor);
+
+        // test play returns to the editor instead of the game over screen
+        app.add_systems(
+            Update,
+            stop_test_play
+                .run_if(in_state(GameState::Game))
+                .run_if(resource_equals(TestPlay(true))),
+        )
+        .add_systems(
+            OnEnter(GameState::GameOver),
+            back_to_editor.run_if(resource_equals(TestPlay(true))),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EditorTool {
+    Wall,
+    Spawn,
+    Portal,
+    Food,
+    Erase,
+}
+
+impl EditorTool {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Wall => "Wall",
+            Self::Spawn => "Spawn",
+            Self::Portal => "Portal",
+            Self::Food => "Food",
+            Self::Erase => "Erase",
+        }
+    }
+}
+
+/// The level being edited. Kept between visits so that test plays and trips to
+/// the menu do not lose work.
+#[derive(Resource)]
+struct Editor {
+    level: Level,
+    tool: EditorTool,
+    /// First end of a portal pair waiting for its second click.
+    pending_portal: Option<Position>,
+    status: String,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self {
+            level: Level {
+                name: "Custom".to_string(),
+                ..default()
+            },
+            tool: EditorTool::Wall,
+            pending_portal: None,
+            status: String::new(),
+        }
+    }
+}
+
+/// Set while a level from the editor is being played.
+#[derive(Resource, Debug, PartialEq, Eq)]
+struct TestPlay(bool);
+
+#[derive(Component)]
+struct EditorTile;
+
+#[derive(Component)]
+struct EditorUI;
+
+#[derive(Component)]
+struct ToolbarText;
+
+fn setup_editor(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    palette: Res<Palette>,
+    mut editor: ResMut<Editor>,
+    mut test_play: ResMut<TestPlay>,
+    mut current_level: ResMut<CurrentLevel>,
+) {
+    test_play.0 = false;
+    current_level.0 = None;
+    // redraw everything
+    editor.set_changed();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.0),
+                    width: Val::Percent(100.0),
+                    height: Val::Px(HUD_HEIGHT),
+                    align_items: AlignItems::Center,
+                    padding: UiRect::horizontal(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            Themed(Role::Panel),
+            EditorUI,
+        ))
+        .with_children(|children| {
+            children.spawn((
+                TextBundle::from_sections([
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/KnightWarrior.otf"),
+                            font_size: 16.0,
+                            color: palette.color(Role::TextAccent),
+                        },
+                    ),
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/KnightWarrior.otf"),
+                            font_size: 12.0,
+                            color: palette.color(Role::TextMuted),
+                        },
+                    ),
+                ]),
+                ToolbarText,
+            ));
+        });
+}
+
+fn select_tool(keyboard_input: Res<Input<KeyCode>>, mut editor: ResMut<Editor>) {
+    let tool = if keyboard_input.just_pressed(KeyCode::Key1) {
+        EditorTool::Wall
+    } else if keyboard_input.just_pressed(KeyCode::Key2) {
+        EditorTool::Spawn
+    } else if keyboard_input.just_pressed(KeyCode::Key3) {
+        EditorTool::Portal
+    } else if keyboard_input.just_pressed(KeyCode::Key4) {
+        EditorTool::Food
+    } else if keyboard_input.just_pressed(KeyCode::Key5) {
+        EditorTool::Erase
+    } else {
+        return;
+    };
+
+    editor.tool = tool;
+    editor.pending_portal = None;
+}
+
+fn resize_arena(keyboard_input: Res<Input<KeyCode>>, mut editor: ResMut<Editor>) {
+    let (mut width, mut height) = (editor.level.width, editor.level.height);
+    if keyboard_input.just_pressed(KeyCode::Right) {
+        width += 1;
+    }
+    if keyboard_input.just_pressed(KeyCode::Left) {
+        width -= 1;
+    }
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        height += 1;
+    }
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        height -= 1;
+    }
+    let width = width.clamp(MIN_ARENA_SIZE, MAX_ARENA_SIZE);
+    let height = height.clamp(MIN_ARENA_SIZE, MAX_ARENA_SIZE);
+    if (width, height) == (editor.level.width, editor.level.height) {
+        return;
+    }
+
+    let editor = editor.as_mut();
+    let level = &mut editor.level;
+    level.width = width;
+    level.height = height;
+
+    // drop everything that no longer fits, the spawn is pulled back inside
+    let fits = |pos: &Position| pos.x < width as i32 && pos.y < height as i32;
+    level.walls.retain(fits);
+    level.food_spots.retain(fits);
+    level.portals.retain(|ends| ends.iter().all(fits));
+    // a path hazard goes if any of its waypoints falls off the board
+    level.hazards = level
+        .hazards
+        .iter()
+        .filter(|hazard| hazard.fits_in(level))
+        .cloned()
+        .collect();
+    level.spawn = Position {
+        x: level.spawn.x.min(width as i32 - 1),
+        y: level.spawn.y.min(height as i32 - 1),
+    };
+    clear_cell(level, level.spawn);
+    update_food_placement(level);
+    if editor.pending_portal.is_some_and(|pos| !fits(&pos)) {
+        editor.pending_portal = None;
+    }
+}
+
+fn paint(
+    mouse_input: Res<Input<MouseButton>>,
+    mut editor: ResMut<Editor>,
+    arena: Res<Arena>,
+    primary_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    let (Ok(window), Ok((camera, camera_transform))) =
+        (primary_query.get_single(), camera_query.get_single())
+    else {
+        return;
+    };
+    let Some(pos) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))
+        .and_then(|world| world_to_arena(world, window, &arena))
+    else {
+        return;
+    };
+
+    // walls, food and erasing can be dragged, the other tools are single clicks
+    let tool = if mouse_input.pressed(MouseButton::Right) {
+        EditorTool::Erase
+    } else if mouse_input.just_pressed(MouseButton::Left)
+        || mouse_input.pressed(MouseButton::Left)
+            && matches!(
+                editor.tool,
+                EditorTool::Wall | EditorTool::Food | EditorTool::Erase
+            )
+    {
+        editor.tool
+    } else {
+        return;
+    };
+
+    // only flag the resource when something changes, the tiles are redrawn on change
+    let level_changed = {
+        let editor = editor.bypass_change_detection();
+        // any edit clears the last save/load message
+        let status = std::mem::take(&mut editor.status);
+        let changed = match tool {
+            EditorTool::Wall => {
+                pos != editor.level.spawn && !editor.level.is_wall(&pos) && {
+                    clear_cell(&mut editor.level, pos);
+                    editor.level.walls.insert(pos)
+                }
+            }
+            EditorTool::Food => {
+                pos != editor.level.spawn && !editor.level.food_spots.contains(&pos) && {
+                    clear_cell(&mut editor.level, pos);
+                    editor.level.food_spots.push(pos);
+                    true
+                }
+            }
+            EditorTool::Erase => pos != editor.level.spawn && clear_cell(&mut editor.level, pos),
+            EditorTool::Spawn => {
+                if pos == editor.level.spawn {
+                    editor.level.facing = turn_clockwise(editor.level.facing);
+                } else {
+                    clear_cell(&mut editor.level, pos);
+                    editor.level.spawn = pos;
+                }
+                true
+            }
+            EditorTool::Portal => {
+                if pos == editor.level.spawn || editor.pending_portal == Some(pos) {
+                    false
+                } else if let Some(first) = editor.pending_portal.take() {
+                    clear_cell(&mut editor.level, pos);
+                    editor.level.portals.push([first, pos]);
+                    true
+                } else if editor.level.portals.len() >= MAX_PORTAL_PAIRS {
+                    editor.status = format!("At most {MAX_PORTAL_PAIRS} portal pairs");
+                    true
+                } else {
+                    clear_cell(&mut editor.level, pos);
+                    editor.pending_portal = Some(pos);
+                    true
+                }
+            }
+        };
+
+        if changed {
+            update_food_placement(&mut editor.level);
+        } else {
+            editor.status = status;
+        }
+        changed
+    };
+
+    if level_changed {
+        editor.set_changed();
+    }
+}
+
+fn save_or_load(keyboard_input: Res<Input<KeyCode>>, mut editor: ResMut<Editor>) {
+    if !keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::S) {
+        editor.pending_portal = None;
+        let source = editor.level.to_string();
+        editor.status = match storage::save(LEVEL_KEY, &source) {
+            Ok(()) => "Saved".to_string(),
+            Err(err) => format!("Save failed: {err}"),
+        };
+    } else if keyboard_input.just_pressed(KeyCode::L) {
+        let loaded = storage::load(LEVEL_KEY)
+            .ok_or_else(|| "no saved level".to_string())
+            .and_then(|source| Level::parse(&source).map_err(|err| err.to_string()));
+        match loaded {
+            Ok(level) => {
+                editor.level = level;
+                editor.pending_portal = None;
+                editor.status = "Loaded".to_string();
+            }
+            Err(err) => editor.status = format!("Load failed: {err}"),
+        }
+    } else if keyboard_input.just_pressed(KeyCode::N) {
+        *editor = Editor {
+            tool: editor.tool,
+            ..default()
+        };
+    }
+}
+
+fn start_test_play(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut editor: ResMut<Editor>,
+    mut levels: ResMut<Assets<Level>>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut test_play: ResMut<TestPlay>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        editor.pending_portal = None;
+        current_level.0 = Some(levels.add(editor.level.clone()));
+        test_play.0 = true;
+        next_state.set(GameState::Game);
+    }
+}
+
+fn leave_editor(keyboard_input: Res<Input<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::Menu);
+    }
+}
+
+fn stop_test_play(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::Editor);
+    }
+}
+
+fn back_to_editor(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Editor);
+}
+
+/// The grid scaling and translation systems read the arena, keep it in sync.
+fn sync_arena(editor: Res<Editor>, mut arena: ResMut<Arena>) {
+    arena.0 = editor.level.clone();
+}
+
+fn draw_tiles(
+    mut commands: Commands,
+    editor: Res<Editor>,
+    palette: Res<Palette>,
+    tile_query: Query<Entity, With<EditorTile>>,
+) {
+    for entity in tile_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let level = &editor.level;
+    let mut tile = |pos: Position, color: Color, size: f32| {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite { color, ..default() },
+                ..default()
+            },
+            pos,
+            Size::square(size),
+            EditorTile,
+        ));
+    };
+
+    for x in 0..level.width as i32 {
+        for y in 0..level.height as i32 {
+            tile(Position { x, y }, palette.color(Role::Tile), 0.95);
+        }
+    }
+    for wall in level.walls.iter() {
+        tile(*wall, palette.color(Role::Wall), 1.0);
+    }
+    for spot in level.food_spots.iter() {
+        tile(*spot, palette.color(Role::Food), 0.5);
+    }
+    for (pair, ends) in level.portals.iter().enumerate() {
+        for end in ends {
+            tile(*end, PORTAL_COLORS[pair % PORTAL_COLORS.len()], 0.8);
+        }
+    }
+    // hazards are written by hand in the level file, the editor keeps them,
+    // shows where they start and erases them with the cells they cover
+    for hazard in level.hazards.iter() {
+        for cell in hazard.cells_at(hazard.origin) {
+            tile(
+                cell,
+                palette.color(hazard.kind.role()).with_a(0.6),
+                hazard.kind.tile_size(),
+            );
+        }
+    }
+    if let Some(pending) = editor.pending_portal {
+        tile(
+            pending,
+            PORTAL_COLORS[level.portals.len() % PORTAL_COLORS.len()].with_a(0.5),
+            0.8,
+        );
+    }
+
+    let spawn_color = palette.color(Role::SnakeHead);
+    tile(level.spawn, spawn_color, 0.8);
+    // a small notch shows where the snake will head
+    let (dx, dy) = level.facing.offset();
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: spawn_color,
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            ..default()
+        },
+        Position {
+            x: level.spawn.x + dx,
+            y: level.spawn.y + dy,
+        },
+        Size::square(0.3),
+        EditorTile,
+    ));
+}
+
+fn update_toolbar(editor: Res<Editor>, mut query: Query<&mut Text, With<ToolbarText>>) {
+    let Ok(mut text) = query.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = format!(
+        "{} {}x{}  ",
+        editor.tool.label(),
+        editor.level.width,
+        editor.level.height
+    );
+    text.sections[1].value = if editor.status.is_empty() {
+        "1-5 tool  arrows resize  enter play  ctrl+s/l/n save/load/new  esc menu".to_string()
+    } else {
+        editor.status.clone()
+    };
+}
+
+fn cleanup_editor(
+    mut commands: Commands,
+    query: Query<Entity, Or<(With<EditorTile>, With<EditorUI>)>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Removes whatever sits on `pos`, apart from the spawn point. A hazard goes
+/// if any cell it starts on is cleared.
+fn clear_cell(level: &mut Level, pos: Position) -> bool {
+    let had_wall = level.walls.remove(&pos);
+    let food_spots = level.food_spots.len();
+    level.food_spots.retain(|spot| *spot != pos);
+    let portals = level.portals.len();
+    level.portals.retain(|ends| !ends.contains(&pos));
+    let hazards = level.hazards.len();
+    level
+        .hazards
+        .retain(|hazard| !hazard.cells_at(hazard.origin).any(|cell| cell == pos));
+
+    had_wall
+        || food_spots != level.food_spots.len()
+        || portals != level.portals.len()
+        || hazards != level.hazards.len()
+}
+
+/// Painted food spots replace random food placement.
+fn update_food_placement(level: &mut Level) {
+    level.rules.food = if level.food_spots.is_empty() {
+        FoodPlacement::Random
+    } else {
+        FoodPlacement::Spots
+    };
+}
+
+fn turn_clockwise(direction: Direction) -> Direction {
+    match direction {
+        Direction::Up => Direction::Right,
+        Direction::Right => Direction::Down,
+        Direction::Down => Direction::Left,
+        Direction::Left => Direction::Up,
+    }
+}
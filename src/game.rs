@@ -1,54 +1,138 @@
-use std::time::Duration;
-
-use bevy::{prelude::*, time::common_conditions::on_timer, window::PrimaryWindow};
-use rand::random;
-
-use crate::GameState;
-
-const SNAKE_HEAD_COLOR: Color = Color::WHITE;
-const FOOD_COLOR: Color = Color::GREEN;
-const SNAKE_BODY_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
-
-const ARENA_WIDTH: u32 = 10;
-const ARENA_HEIGHT: u32 = 10;
+use std::{collections::HashSet, iter, time::Duration};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use enum_iterator::Sequence;
+use rand::{seq::SliceRandom, thread_rng};
+
+use crate::{
+    bot::Bot,
+    death::CameraShake,
+    grid_movement,
+    hex::hex_round,
+    hud::HUD_HEIGHT,
+    level::{spawn_wall, Arena, CurrentLevel, FoodPlacement},
+    storage,
+    theme::{Palette, Role, Themed},
+    tron::tron_round,
+    GameState, Players, RoundState, MAX_PLAYERS,
+};
+
+const BITE_PENALTY_KEY: &str = "settings.bite_penalty";
+/// Highest bite penalty the menu offers before going back to none.
+const MAX_BITE_PENALTY: usize = 3;
+
+/// Head and body colours of the players after the first, who takes them from
+/// the theme.
+const RIVAL_COLORS: [(Color, Color); MAX_PLAYERS - 1] = [
+    (Color::CYAN, Color::rgb(0.1, 0.35, 0.4)),
+    (Color::GOLD, Color::rgb(0.45, 0.35, 0.1)),
+    (Color::PINK, Color::rgb(0.45, 0.2, 0.35)),
+];
+
+/// Left, up, right and down keys of each player in a versus round.
+const PLAYER_KEYS: [[KeyCode; 4]; MAX_PLAYERS] = [
+    [KeyCode::A, KeyCode::W, KeyCode::D, KeyCode::S],
+    [KeyCode::Left, KeyCode::Up, KeyCode::Right, KeyCode::Down],
+    [KeyCode::J, KeyCode::I, KeyCode::L, KeyCode::K],
+    [
+        KeyCode::Numpad4,
+        KeyCode::Numpad8,
+        KeyCode::Numpad6,
+        KeyCode::Numpad5,
+    ],
+];
+/// A lone snake steers with the arrows.
+const SOLO_KEYS: [KeyCode; 4] = PLAYER_KEYS[1];
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Left,
+    Direction::Up,
+    Direction::Right,
+    Direction::Down,
+];
+
+/// Below this many pixels per cell the arena scrolls instead of shrinking.
+const MIN_TILE_SIZE: f32 = 14.0;
+const SCROLL_TILE_SIZE: f32 = 24.0;
+
+const POINTS_PER_SPEED_LEVEL: usize = 5;
+const MIN_MOVEMENT_INTERVAL: Duration = Duration::from_millis(120);
+const MOVEMENT_INTERVAL_STEP: Duration = Duration::from_millis(30);
 
 pub struct GamePlayingPlugin;
 
 impl Plugin for GamePlayingPlugin {
     fn build(&self, app: &mut App) {
         // resources
-        app.insert_resource(SnakeBody::default())
-            .insert_resource(TailPosition::default())
-            .insert_resource(LastInputDirection(None))
-            .insert_resource(ScoreBoard(0));
+        app.insert_resource(HighScore(0))
+            .insert_resource(EndlessBest(0))
+            .insert_resource(SpeedLevel(1))
+            .insert_resource(RoundTime::default())
+            .insert_resource(RoundOutcome::default())
+            .insert_resource(MovementTimer::default())
+            .insert_resource(FoodSpawnTimer::default())
+            .insert_resource(BiteRule::default())
+            .insert_resource(BitePenalty::load());
 
         // events
         app.add_event::<GrowthEvent>();
         app.add_event::<GameOverEvent>();
+        app.add_event::<CutEvent>();
 
-        // handler
-        app.add_systems(OnEnter(GameState::Game), setup);
+        // round lifecycle
+        app.configure_sets(
+            OnEnter(GameState::Game),
+            (RoundSetup::Reset, RoundSetup::Spawn).chain(),
+        )
+        .add_systems(
+            OnEnter(GameState::Game),
+            (
+                reset_round.in_set(RoundSetup::Reset),
+                setup.in_set(RoundSetup::Spawn).run_if(grid_movement),
+                start_countdown.after(RoundSetup::Spawn),
+            ),
+        )
+        .add_systems(OnExit(GameState::Game), (cleanup_round, end_round));
+
+        // direction input is buffered during the countdown as well
+        app.add_systems(
+            Update,
+            snake_movement_input
+                .before(SnakeStep)
+                .run_if(in_state(RoundState::Countdown).or_else(in_state(RoundState::Playing)))
+                .run_if(grid_movement),
+        );
 
         app.add_systems(
             Update,
             (
-                snake_movement.run_if(on_timer(Duration::from_millis(350))),
-                spawn_food.run_if(on_timer(Duration::from_millis(700))),
-                snake_movement_input.before(snake_movement),
-                snake_eating.after(snake_movement),
-                snake_growth.after(snake_eating),
-                update_scoreboard.after(snake_eating),
-                game_over.after(snake_movement),
+                tick_round_timers,
+                update_movement_speed
+                    .run_if(resource_changed::<SpeedLevel>())
+                    .before(tick_round_timers),
+                (
+                    snake_movement
+                        .in_set(SnakeStep)
+                        .run_if(movement_tick)
+                        .after(tick_round_timers),
+                    snake_eating.after(snake_movement),
+                    snake_growth.after(snake_eating),
+                )
+                    .run_if(grid_movement),
+                spawn_food
+                    .run_if(food_spawn_tick)
+                    .run_if(not(tron_round))
+                    .run_if(not(hex_round))
+                    .after(tick_round_timers),
+                game_over.after(SnakeStep),
             )
-                .run_if(in_state(GameState::Game)),
+                .run_if(in_state(RoundState::Playing)),
         )
-        // .add_systems(Update, update_scoreboard.run_if(in_state(GameState::Game)))
         .add_systems(PostUpdate, (size_scaling, position_translation));
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum Direction {
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Direction {
     Left,
     Up,
     Right,
@@ -56,7 +140,7 @@ enum Direction {
 }
 
 impl Direction {
-    fn opposite(&self) -> Self {
+    pub(crate) fn opposite(&self) -> Self {
         match self {
             Self::Left => Self::Right,
             Self::Right => Self::Left,
@@ -64,32 +148,74 @@ impl Direction {
             Self::Down => Self::Up,
         }
     }
+
+    /// Grid step taken when moving one cell in this direction.
+    pub(crate) fn offset(&self) -> (i32, i32) {
+        match self {
+            Self::Left => (-1, 0),
+            Self::Right => (1, 0),
+            Self::Up => (0, 1),
+            Self::Down => (0, -1),
+        }
+    }
 }
 
 #[derive(Component)]
-struct SnakeHead {
+pub(crate) struct SnakeHead {
     direction: Direction,
+    /// Turn picked since the last step, taken on the next one.
+    queued: Option<Direction>,
+    /// Cell the tail left on the last step, where the snake grows.
+    tail: Option<Position>,
+}
+
+impl SnakeHead {
+    pub(crate) fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Cell the tail left on the last step.
+    pub(crate) fn tail(&self) -> Option<Position> {
+        self.tail
+    }
+
+    /// Turns on the next step, replacing any turn already picked. Reversing
+    /// into the neck is ignored.
+    pub(crate) fn steer(&mut self, direction: Direction) {
+        if direction != self.direction.opposite() {
+            self.queued = Some(direction);
+        }
+    }
 }
 
-#[derive(Resource, Default, Debug, Deref, DerefMut)]
-struct SnakeBody(Vec<Entity>);
+/// Which player a snake belongs to, counted from 0.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Player(pub(crate) usize);
+
+/// Segments of a snake, head first. Lives on the head entity.
+#[derive(Component, Default, Debug, Deref, DerefMut)]
+pub(crate) struct SnakeBody(pub(crate) Vec<Entity>);
 
 #[derive(Component)]
 struct SnakeBodyPart;
 
+/// On the head of a snake that died this round. Only these fall apart.
 #[derive(Component)]
-struct Food;
+pub(crate) struct Dead;
 
-#[derive(Component, Debug, Clone, Copy, PartialEq)]
-struct Position {
-    x: i32,
-    y: i32,
+#[derive(Component)]
+pub(crate) struct Food;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Position {
+    pub(crate) x: i32,
+    pub(crate) y: i32,
 }
 
 #[derive(Component)]
-struct Size {
-    width: f32,
-    height: f32,
+pub(crate) struct Size {
+    pub(crate) width: f32,
+    pub(crate) height: f32,
 }
 
 impl Size {
@@ -101,321 +227,679 @@ impl Size {
     }
 }
 
+/// The snake with this head ate.
 #[derive(Event)]
-struct GrowthEvent;
+pub(crate) struct GrowthEvent(pub(crate) Entity);
 
+/// The snake with this head died.
 #[derive(Event)]
-struct GameOverEvent;
+pub(crate) struct GameOverEvent(pub(crate) Entity);
 
-#[derive(Resource, Default)]
-struct TailPosition(Option<Position>);
+/// The snake with this head bit itself and lost this many segments.
+#[derive(Event)]
+pub(crate) struct CutEvent {
+    pub(crate) snake: Entity,
+    pub(crate) segments: usize,
+}
+
+/// What biting its own body does to a grid snake, picked in the menu.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq, Sequence)]
+pub(crate) enum BiteRule {
+    /// The round is over.
+    #[default]
+    Death,
+    /// The snake is cut where it bit and the severed segments vanish.
+    Cut,
+    /// Cut, and the severed segments are left behind as food.
+    CutToFood,
+    /// Cut, and the severed segments are left behind as walls.
+    CutToWalls,
+}
+
+impl BiteRule {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Self::Death => "Death",
+            Self::Cut => "Cut",
+            Self::CutToFood => "Food",
+            Self::CutToWalls => "Walls",
+        }
+    }
+}
 
+/// Points the first player loses for each segment bitten off, picked in the
+/// menu.
 #[derive(Resource)]
-struct LastInputDirection(Option<Direction>);
+pub(crate) struct BitePenalty(pub(crate) usize);
 
+impl Default for BitePenalty {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl BitePenalty {
+    /// The penalty after this one, going back to none after the highest.
+    pub(crate) fn next(&self) -> Self {
+        Self((self.0 + 1) % (MAX_BITE_PENALTY + 1))
+    }
+
+    fn load() -> Self {
+        storage::load(BITE_PENALTY_KEY)
+            .and_then(|value| value.trim().parse().ok())
+            .filter(|penalty| *penalty <= MAX_BITE_PENALTY)
+            .map(Self)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self) {
+        if let Err(err) = storage::save(BITE_PENALTY_KEY, &self.0.to_string()) {
+            warn!("Could not save the bite penalty: {err}");
+        }
+    }
+}
+
+/// Best score reached since the game was started.
 #[derive(Resource)]
-struct ScoreBoard(usize);
-
-fn setup(mut commands: Commands, mut body: ResMut<SnakeBody>, asset_server: Res<AssetServer>) {
-    // scoreboard
-    commands.spawn(
-        TextBundle::from_sections([
-            TextSection::new(
-                "Score: ",
-                TextStyle {
-                    font_size: 24.0,
-                    color: Color::YELLOW,
-                    font: asset_server.load("fonts/KnightWarrior.otf"),
-                    ..default()
-                },
-            ),
-            TextSection::new(
-                "0",
-                TextStyle {
-                    font_size: 24.0,
-                    color: Color::YELLOW,
-                    font: asset_server.load("fonts/KnightWarrior.otf"),
-                    ..default()
-                },
-            ),
-        ])
-        .with_style(Style {
-            position_type: PositionType::Absolute,
-            right: Val::Px(0.0),
-            top: Val::Px(0.0),
-            margin: UiRect::axes(Val::Px(10.0), Val::Px(10.0)),
-            ..default()
-        }),
-    );
-
-    // snake
-
-    let spawn_pos_x = ARENA_WIDTH as i32 / 2;
-    let spawn_pos_y = ARENA_WIDTH as i32 / 2;
-
-    *body = SnakeBody(vec![
-        commands
+pub(crate) struct HighScore(pub(crate) usize);
+
+/// Best score of a classic round outside the campaign since the game was
+/// started. Score unlocks go by this, so modes that score faster don't count.
+#[derive(Resource)]
+pub(crate) struct EndlessBest(pub(crate) usize);
+
+/// Rises every few points and makes the snake move faster.
+#[derive(Resource)]
+pub(crate) struct SpeedLevel(pub(crate) u32);
+
+impl SpeedLevel {
+    pub(crate) fn for_score(score: usize) -> Self {
+        Self(1 + (score / POINTS_PER_SPEED_LEVEL) as u32)
+    }
+
+    fn movement_interval(&self, base: Duration) -> Duration {
+        base.saturating_sub(MOVEMENT_INTERVAL_STEP * (self.0 - 1))
+            .max(MIN_MOVEMENT_INTERVAL)
+    }
+}
+
+/// Time spent in the current round. Only flagged as changed once per whole
+/// second so that the HUD does not rebuild its text every frame.
+#[derive(Resource, Default)]
+pub(crate) struct RoundTime(Duration);
+
+impl RoundTime {
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.0
+    }
+}
+
+/// How many snakes started the round, the first `humans` of them steered from
+/// the keyboard, and how a versus round ended.
+#[derive(Resource)]
+pub(crate) struct RoundOutcome {
+    pub(crate) players: usize,
+    pub(crate) humans: usize,
+    /// Snakes still alive when the round ended.
+    pub(crate) survivors: usize,
+    /// Set when a single snake was left.
+    pub(crate) winner: Option<usize>,
+}
+
+impl Default for RoundOutcome {
+    fn default() -> Self {
+        Self {
+            players: 1,
+            humans: 1,
+            survivors: 0,
+            winner: None,
+        }
+    }
+}
+
+impl RoundOutcome {
+    pub(crate) fn is_versus(&self) -> bool {
+        self.players > 1
+    }
+
+    pub(crate) fn player_name(&self, player: usize) -> String {
+        if player < self.humans {
+            format!("Player {}", player + 1)
+        } else {
+            format!("Bot {}", player - self.humans + 1)
+        }
+    }
+}
+
+/// Paces the snake: one grid step every time it finishes.
+#[derive(Resource, Deref, DerefMut)]
+pub(crate) struct MovementTimer(Timer);
+
+impl Default for MovementTimer {
+    fn default() -> Self {
+        Self(Timer::new(Duration::from_millis(350), TimerMode::Repeating))
+    }
+}
+
+/// Paces food spawning.
+#[derive(Resource, Deref, DerefMut)]
+struct FoodSpawnTimer(Timer);
+
+impl Default for FoodSpawnTimer {
+    fn default() -> Self {
+        Self(Timer::new(Duration::from_millis(700), TimerMode::Repeating))
+    }
+}
+
+/// Ordering of the systems that prepare a round on `OnEnter(GameState::Game)`.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum RoundSetup {
+    /// Round resources are put back to their initial values.
+    Reset,
+    /// The arena and the snake are spawned.
+    Spawn,
+}
+
+/// The grid step of every snake; steering comes before it, anything reacting
+/// to the new positions after.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct SnakeStep;
+
+/// Marks every entity that belongs to a single round; they are all despawned
+/// when leaving `GameState::Game`.
+#[derive(Component)]
+pub(crate) struct RoundCleanup;
+
+/// Puts every round resource back to its initial value so that each visit to
+/// `GameState::Game` starts a fresh round.
+fn reset_round(
+    mut speed_level: ResMut<SpeedLevel>,
+    mut round_time: ResMut<RoundTime>,
+    mut outcome: ResMut<RoundOutcome>,
+    mut movement_timer: ResMut<MovementTimer>,
+    mut food_spawn_timer: ResMut<FoodSpawnTimer>,
+    mut growth_events: ResMut<Events<GrowthEvent>>,
+    mut game_over_events: ResMut<Events<GameOverEvent>>,
+    mut cut_events: ResMut<Events<CutEvent>>,
+) {
+    *speed_level = SpeedLevel(1);
+    *round_time = RoundTime::default();
+    *outcome = RoundOutcome::default();
+    *movement_timer = MovementTimer::default();
+    *food_spawn_timer = FoodSpawnTimer::default();
+    growth_events.clear();
+    game_over_events.clear();
+    cut_events.clear();
+}
+
+fn start_countdown(mut next_round_state: ResMut<NextState<RoundState>>) {
+    next_round_state.set(RoundState::Countdown);
+}
+
+fn end_round(mut next_round_state: ResMut<NextState<RoundState>>) {
+    next_round_state.set(RoundState::Inactive);
+}
+
+fn cleanup_round(mut commands: Commands, query: Query<Entity, With<RoundCleanup>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn tick_round_timers(
+    time: Res<Time>,
+    mut round_time: ResMut<RoundTime>,
+    mut movement_timer: ResMut<MovementTimer>,
+    mut food_spawn_timer: ResMut<FoodSpawnTimer>,
+) {
+    let previous_secs = round_time.0.as_secs();
+    round_time.bypass_change_detection().0 += time.delta();
+    if round_time.0.as_secs() != previous_secs {
+        round_time.set_changed();
+    }
+
+    movement_timer.tick(time.delta());
+    food_spawn_timer.tick(time.delta());
+}
+
+fn update_movement_speed(
+    arena: Res<Arena>,
+    speed_level: Res<SpeedLevel>,
+    mut movement_timer: ResMut<MovementTimer>,
+) {
+    movement_timer.set_duration(speed_level.movement_interval(arena.rules.movement_interval));
+}
+
+pub(crate) fn movement_tick(timer: Res<MovementTimer>) -> bool {
+    timer.just_finished()
+}
+
+fn food_spawn_tick(timer: Res<FoodSpawnTimer>) -> bool {
+    timer.just_finished()
+}
+
+/// Head and body colours of a player.
+pub(crate) fn player_colors(player: usize, palette: &Palette) -> (Color, Color) {
+    match player.checked_sub(1) {
+        None => (
+            palette.color(Role::SnakeHead),
+            palette.color(Role::SnakeBody),
+        ),
+        Some(rival) => RIVAL_COLORS[rival % RIVAL_COLORS.len()],
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    arena: Res<Arena>,
+    players: Res<Players>,
+    current_level: Res<CurrentLevel>,
+    mut outcome: ResMut<RoundOutcome>,
+) {
+    // campaign and editor levels are always played alone
+    let players = if current_level.0.is_some() {
+        Players::default()
+    } else {
+        *players
+    };
+    outcome.players = players.humans + players.bots;
+    outcome.humans = players.humans;
+
+    for (index, (spawn_pos, facing)) in arena.spawn_points(outcome.players).into_iter().enumerate()
+    {
+        let (head_color, _) = player_colors(index, &palette);
+        let (dx, dy) = facing.offset();
+
+        let head = commands
             .spawn((
                 SpriteBundle {
                     sprite: Sprite {
-                        color: SNAKE_HEAD_COLOR,
+                        color: head_color,
                         ..default()
                     },
                     ..default()
                 },
                 SnakeHead {
-                    direction: Direction::Up,
+                    direction: facing,
+                    queued: None,
+                    tail: None,
                 },
+                Player(index),
                 SnakeBodyPart,
-                Position {
-                    x: spawn_pos_x,
-                    y: spawn_pos_y,
-                },
+                spawn_pos,
                 Size::square(0.8),
+                RoundCleanup,
             ))
-            .id(),
-        spawn_body(
-            commands,
+            .id();
+        let neck = spawn_body(
+            &mut commands,
             Position {
-                x: spawn_pos_x,
-                y: spawn_pos_y - 1,
+                x: spawn_pos.x - dx,
+                y: spawn_pos.y - dy,
             },
-        ),
-    ]);
+            Player(index),
+            &palette,
+        );
+        commands.entity(head).insert(SnakeBody(vec![head, neck]));
+        if index >= players.humans {
+            commands.entity(head).insert(Bot);
+        }
+    }
 }
 
 fn spawn_food(
     mut commands: Commands,
-    body: ResMut<SnakeBody>,
+    arena: Res<Arena>,
     food_query: Query<Entity, With<Food>>,
-    mut positions_query: Query<&mut Position>,
+    positions_query: Query<&Position>,
 ) {
     if food_query.iter().next().is_none() {
-        let mut food_pos = Position {
-            x: (random::<f32>() * ARENA_WIDTH as f32) as i32,
-            y: (random::<f32>() * ARENA_HEIGHT as f32) as i32,
+        let occupied = positions_query
+            .iter()
+            .copied()
+            .collect::<HashSet<Position>>();
+        let is_free = |pos: &Position| !arena.is_wall(pos) && !occupied.contains(pos);
+
+        let candidates = match arena.rules.food {
+            FoodPlacement::Spots => arena
+                .food_spots
+                .iter()
+                .copied()
+                .filter(is_free)
+                .collect::<Vec<Position>>(),
+            FoodPlacement::Random => (0..arena.width as i32)
+                .flat_map(|x| (0..arena.height as i32).map(move |y| Position { x, y }))
+                .filter(is_free)
+                .collect::<Vec<Position>>(),
         };
 
-        let body_positions = body
-            .0
-            .iter()
-            .map(|e| *positions_query.get_mut(*e).unwrap())
-            .collect::<Vec<Position>>();
-
-        for body_pos in body_positions.iter() {
-            if *body_pos == food_pos {
-                food_pos = Position {
-                    x: (random::<f32>() * ARENA_WIDTH as f32) as i32,
-                    y: (random::<f32>() * ARENA_HEIGHT as f32) as i32,
-                }
-            }
-        }
+        let Some(food_pos) = candidates.choose(&mut thread_rng()).copied() else {
+            return;
+        };
 
         commands.spawn((
-            SpriteBundle {
-                sprite: Sprite {
-                    color: FOOD_COLOR,
-                    ..default()
-                },
-                ..default()
-            },
+            SpriteBundle::default(),
+            Themed(Role::Food),
             Food,
             food_pos,
             Size::square(0.8),
+            RoundCleanup,
         ));
     }
 }
 
 fn snake_movement_input(
-    mut last_dir: ResMut<LastInputDirection>,
     keyboard_input: Res<Input<KeyCode>>,
-    snake_query: Query<&SnakeHead>,
+    outcome: Res<RoundOutcome>,
+    mut snake_query: Query<(&Player, &mut SnakeHead), Without<Bot>>,
 ) {
-    if let Ok(snake) = snake_query.get_single() {
-        let dir: Direction = if keyboard_input.pressed(KeyCode::Left) {
-            Direction::Left
-        } else if keyboard_input.pressed(KeyCode::Right) {
-            Direction::Right
-        } else if keyboard_input.pressed(KeyCode::Up) {
-            Direction::Up
-        } else if keyboard_input.pressed(KeyCode::Down) {
-            Direction::Down
+    for (player, mut head) in snake_query.iter_mut() {
+        let keys = if outcome.humans > 1 {
+            PLAYER_KEYS[player.0 % MAX_PLAYERS]
         } else {
-            snake.direction
+            SOLO_KEYS
+        };
+        let Some(dir) = DIRECTIONS
+            .into_iter()
+            .zip(keys)
+            .find(|(_, key)| keyboard_input.pressed(*key))
+            .map(|(dir, _)| dir)
+        else {
+            continue;
         };
 
-        if dir != snake.direction.opposite() && dir != snake.direction && last_dir.0.is_none() {
-            last_dir.0 = Some(dir);
+        if dir != head.direction.opposite() && dir != head.direction && head.queued.is_none() {
+            head.queued = Some(dir);
         }
     }
 }
 
 fn snake_movement(
-    body: ResMut<SnakeBody>,
-    mut maybe_last_dir: ResMut<LastInputDirection>,
-    mut tail_position: ResMut<TailPosition>,
-    mut snake_head_query: Query<(Entity, &mut SnakeHead)>,
+    mut commands: Commands,
+    mut arena: ResMut<Arena>,
+    bite_rule: Res<BiteRule>,
+    mut snake_query: Query<(Entity, &mut SnakeHead, &mut SnakeBody)>,
     mut positions_query: Query<&mut Position>,
     mut game_over_event: EventWriter<GameOverEvent>,
+    mut cut_event: EventWriter<CutEvent>,
 ) {
-    if let Ok((head_entity, mut head)) = snake_head_query.get_single_mut() {
-        let body_positions = body
-            .0
-            .iter()
-            .map(|e| *positions_query.get_mut(*e).unwrap())
-            .collect::<Vec<Position>>();
+    // every snake steps at once and is checked against where the others were
+    // before this step
+    let bodies = snake_query
+        .iter()
+        .map(|(entity, _, body)| {
+            let positions = body
+                .iter()
+                .map(|e| *positions_query.get(*e).unwrap())
+                .collect::<Vec<Position>>();
+            (entity, positions)
+        })
+        .collect::<Vec<(Entity, Vec<Position>)>>();
+    let mut new_heads = Vec::with_capacity(bodies.len());
+
+    for (entity, body_positions) in bodies.iter() {
+        let Ok((_, mut head, mut body)) = snake_query.get_mut(*entity) else {
+            continue;
+        };
 
-        let mut head_pos = positions_query.get_mut(head_entity).unwrap();
+        if let Some(queued) = head.queued.take() {
+            head.direction = queued;
+        }
+
+        // through a portal the head lands far from the neck, so collisions only
+        // compare cells and never assume the segments are adjacent
+        let head_pos = arena.step(&body_positions[0], head.direction);
 
-        if let Some(last_dir) = maybe_last_dir.0 {
-            head.direction = last_dir;
-            maybe_last_dir.0 = None;
+        if arena.is_deadly(&head_pos) {
+            game_over_event.send(GameOverEvent(*entity));
         }
 
-        match head.direction {
-            Direction::Left => {
-                head_pos.x -= 1;
-            }
-            Direction::Right => {
-                head_pos.x += 1;
-            }
-            Direction::Up => {
-                head_pos.y += 1;
-            }
-            Direction::Down => {
-                head_pos.y -= 1;
+        if let Some(index) = body_positions.iter().position(|pos| *pos == head_pos) {
+            if *bite_rule == BiteRule::Death {
+                game_over_event.send(GameOverEvent(*entity));
+            } else {
+                // the bitten segment goes, everything behind it stays where it was
+                let severed = body.split_off(index.max(1));
+                for (segment, pos) in severed.iter().zip(&body_positions[index.max(1)..]).skip(1) {
+                    match *bite_rule {
+                        BiteRule::CutToFood => {
+                            commands.entity(*segment).remove::<SnakeBodyPart>().insert((
+                                Food,
+                                Sprite::default(),
+                                Themed(Role::Food),
+                                Size::square(0.8),
+                            ));
+                        }
+                        BiteRule::CutToWalls => {
+                            arena.walls.insert(*pos);
+                            spawn_wall(&mut commands, *pos);
+                            commands.entity(*segment).despawn_recursive();
+                        }
+                        _ => commands.entity(*segment).despawn_recursive(),
+                    }
+                }
+                if let Some(bitten) = severed.first() {
+                    commands.entity(*bitten).despawn_recursive();
+                }
+                cut_event.send(CutEvent {
+                    snake: *entity,
+                    segments: severed.len(),
+                });
             }
-        };
+        }
 
-        if head_pos.x < 0
-            || head_pos.y < 0
-            || head_pos.x as u32 >= ARENA_WIDTH
-            || head_pos.y as u32 >= ARENA_HEIGHT
+        if bodies
+            .iter()
+            .any(|(other, positions)| other != entity && positions.contains(&head_pos))
         {
-            game_over_event.send(GameOverEvent);
+            game_over_event.send(GameOverEvent(*entity));
         }
 
-        if body_positions.contains(&head_pos) {
-            game_over_event.send(GameOverEvent);
+        for (part, pos) in body
+            .iter()
+            .zip(iter::once(head_pos).chain(body_positions.iter().copied()))
+        {
+            *positions_query.get_mut(*part).unwrap() = pos;
         }
 
-        body_positions
-            .iter()
-            .zip(body.0.iter().skip(1))
-            .for_each(|(pos, part)| {
-                *positions_query.get_mut(*part).unwrap() = *pos;
-            });
+        head.tail = body_positions.get(body.len() - 1).copied();
+        new_heads.push((*entity, head_pos));
+    }
 
-        *tail_position = TailPosition(Some(*body_positions.last().unwrap()));
+    // heads meeting in the same cell
+    for (entity, pos) in new_heads.iter() {
+        if new_heads
+            .iter()
+            .any(|(other, other_pos)| other != entity && other_pos == pos)
+        {
+            game_over_event.send(GameOverEvent(*entity));
+        }
     }
 }
 
 fn size_scaling(
+    arena: Res<Arena>,
     primary_query: Query<&Window, With<PrimaryWindow>>,
     mut query: Query<(&Size, &mut Transform)>,
 ) {
     let window = primary_query.get_single().unwrap();
+    let tile = tile_size(window, &arena);
 
     for (size, mut transform) in query.iter_mut() {
-        transform.scale = Vec3::new(
-            size.width / ARENA_WIDTH as f32 * window.width() as f32,
-            size.height / ARENA_HEIGHT as f32 * window.height() as f32,
-            1.0,
-        );
+        transform.scale = Vec3::new(size.width * tile.x, size.height * tile.y, 1.0);
     }
 }
 
 fn position_translation(
+    arena: Res<Arena>,
     primary_query: Query<&Window, With<PrimaryWindow>>,
     mut query: Query<(&Position, &mut Transform)>,
 ) {
-    fn convert(pos: f32, bound_window: f32, bound_game: f32) -> f32 {
-        let tile_size = bound_window / bound_game;
-        pos / bound_game * bound_window - bound_window / 2.0 + tile_size / 2.0
-    }
-
     let window = primary_query.get_single().unwrap();
 
     for (position, mut transform) in query.iter_mut() {
-        transform.translation = Vec3::new(
-            convert(position.x as f32, window.width() as f32, ARENA_WIDTH as f32),
-            convert(
-                position.y as f32,
-                window.height() as f32,
-                ARENA_HEIGHT as f32,
-            ),
-            0.0,
+        let translation = arena_to_world(
+            Vec2::new(position.x as f32, position.y as f32),
+            window,
+            &arena,
         );
+        // each cell is placed on its own, segments on either side of a portal
+        // stay apart; the depth set at spawn decides what is drawn on top
+        transform.translation = translation.extend(transform.translation.z);
+    }
+}
+
+/// Whether the arena is too big to fit the window and scrolls under a camera
+/// following the snake.
+pub(crate) fn is_scrolling(window: &Window, arena: &Arena) -> bool {
+    window.width() / (arena.width as f32) < MIN_TILE_SIZE
+        || (window.height() - HUD_HEIGHT) / (arena.height as f32) < MIN_TILE_SIZE
+}
+
+/// Size of a cell in world units: the largest square letting the whole arena
+/// fit the window below the HUD, or fixed when the arena scrolls.
+pub(crate) fn tile_size(window: &Window, arena: &Arena) -> Vec2 {
+    if is_scrolling(window, arena) {
+        Vec2::splat(SCROLL_TILE_SIZE)
+    } else {
+        Vec2::splat(
+            (window.width() / arena.width as f32)
+                .min((window.height() - HUD_HEIGHT) / arena.height as f32),
+        )
     }
 }
 
-fn spawn_body(mut commands: Commands, position: Position) -> Entity {
+/// Converts arena coordinates, where whole numbers are cell centres, to world space.
+pub(crate) fn arena_to_world(pos: Vec2, window: &Window, arena: &Arena) -> Vec2 {
+    let tile = tile_size(window, arena);
+    let size = tile * Vec2::new(arena.width as f32, arena.height as f32);
+
+    // the arena is centred below the HUD bar
+    pos * tile - size / 2.0 + tile / 2.0 - Vec2::new(0.0, HUD_HEIGHT / 2.0)
+}
+
+/// Cell at a world-space point, if it is inside the arena.
+pub(crate) fn world_to_arena(world: Vec2, window: &Window, arena: &Arena) -> Option<Position> {
+    let cell = (world - arena_bounds(window, arena).min) / tile_size(window, arena);
+    let pos = Position {
+        x: cell.x.floor() as i32,
+        y: cell.y.floor() as i32,
+    };
+    arena.contains(&pos).then_some(pos)
+}
+
+/// World-space rectangle covered by the arena.
+pub(crate) fn arena_bounds(window: &Window, arena: &Arena) -> Rect {
+    let half_cell = Vec2::splat(0.5);
+    let size = Vec2::new(arena.width as f32, arena.height as f32);
+    Rect::from_corners(
+        arena_to_world(-half_cell, window, arena),
+        arena_to_world(size - half_cell, window, arena),
+    )
+}
+
+pub(crate) fn spawn_body(
+    commands: &mut Commands,
+    position: Position,
+    player: Player,
+    palette: &Palette,
+) -> Entity {
+    let (_, color) = player_colors(player.0, palette);
     commands
         .spawn((
             SpriteBundle {
-                sprite: Sprite {
-                    color: SNAKE_BODY_COLOR,
-                    ..default()
-                },
+                sprite: Sprite { color, ..default() },
                 ..default()
             },
             SnakeBodyPart,
             position,
             Size::square(0.6),
+            RoundCleanup,
         ))
         .id()
 }
 
 fn snake_eating(
     mut commands: Commands,
-    snake_query: Query<&Position, With<SnakeHead>>,
+    snake_query: Query<(Entity, &Position), With<SnakeHead>>,
     food_query: Query<(Entity, &Position), With<Food>>,
     mut growth_ev_writer: EventWriter<GrowthEvent>,
 ) {
-    for snake_pos in snake_query.iter() {
+    for (snake, snake_pos) in snake_query.iter() {
         for (food, food_pos) in food_query.iter() {
             if snake_pos == food_pos {
                 commands.entity(food).despawn();
-                growth_ev_writer.send(GrowthEvent);
+                growth_ev_writer.send(GrowthEvent(snake));
             }
         }
     }
 }
 
 fn snake_growth(
-    commands: Commands,
-    last_tail_pos: Res<TailPosition>,
-    mut scoreboard: ResMut<ScoreBoard>,
-    mut body: ResMut<SnakeBody>,
+    mut commands: Commands,
+    palette: Res<Palette>,
+    mut snake_query: Query<(&SnakeHead, &Player, &mut SnakeBody)>,
     mut growth_ev_reader: EventReader<GrowthEvent>,
 ) {
-    if growth_ev_reader.read().next().is_some() {
-        scoreboard.0 += 1;
-        body.0.push(spawn_body(commands, last_tail_pos.0.unwrap()));
+    for GrowthEvent(snake) in growth_ev_reader.read() {
+        let Ok((head, player, mut body)) = snake_query.get_mut(*snake) else {
+            continue;
+        };
+        let Some(tail) = head.tail else {
+            continue;
+        };
+        body.push(spawn_body(&mut commands, tail, *player, &palette));
     }
 }
 
 fn game_over(
     mut commands: Commands,
-    body: ResMut<SnakeBody>,
-    food_query: Query<Entity, With<Food>>,
-    body_parts_query: Query<Entity, With<SnakeBodyPart>>,
     mut game_over_ev_reader: EventReader<GameOverEvent>,
-    asset_server: Res<AssetServer>,
-    mut next_state: ResMut<NextState<GameState>>,
+    mut outcome: ResMut<RoundOutcome>,
+    mut shake: ResMut<CameraShake>,
+    snake_query: Query<(Entity, &Player, &SnakeBody)>,
+    mut next_round_state: ResMut<NextState<RoundState>>,
 ) {
-    if game_over_ev_reader.read().next().is_some() {
-        next_state.set(GameState::GameOver);
+    let dead = game_over_ev_reader
+        .read()
+        .map(|GameOverEvent(snake)| *snake)
+        .collect::<HashSet<Entity>>();
+    if dead.is_empty() {
+        return;
+    }
+    for snake in &dead {
+        commands.entity(*snake).insert(Dead);
+    }
 
-        for e in food_query.iter().chain(body_parts_query.iter()) {
-            commands.entity(e).despawn();
-        }
+    if !outcome.is_versus() {
+        next_round_state.set(RoundState::Dying);
+        return;
+    }
 
-        setup(commands, body, asset_server);
+    // in versus the losers leave the arena and the round goes on until at
+    // most one snake is left, or only bots are
+    let survivors = snake_query
+        .iter()
+        .filter(|(entity, ..)| !dead.contains(entity))
+        .map(|(_, player, _)| player.0)
+        .collect::<Vec<_>>();
+
+    let humans_left = survivors.iter().any(|player| *player < outcome.humans);
+    if survivors.len() <= 1 || !humans_left {
+        // the last to die stay for the death animation
+        outcome.survivors = survivors.len();
+        outcome.winner = (survivors.len() == 1).then(|| survivors[0]);
+        next_round_state.set(RoundState::Dying);
+        return;
     }
-}
 
-fn update_scoreboard(scoreboard: Res<ScoreBoard>, mut query: Query<&mut Text>) {
-    let mut text = query.single_mut();
-    text.sections[1].value = scoreboard.0.to_string();
+    for (entity, _, body) in snake_query.iter() {
+        if dead.contains(&entity) {
+            for segment in body.iter() {
+                commands.entity(*segment).despawn_recursive();
+            }
+        }
+    }
+    shake.start();
 }
@@ -1,6 +1,11 @@
 use bevy::{app::AppExit, prelude::*};
 
-use crate::GameState;
+use crate::{
+    campaign::CampaignRun,
+    game::RoundOutcome,
+    theme::{Role, Themed},
+    GameState,
+};
 
 pub struct GameOverPlugin;
 
@@ -21,10 +26,26 @@ struct PlayAgain;
 #[derive(Component)]
 struct Quit;
 
+/// Back to the level list, only shown during a campaign.
+#[derive(Component)]
+struct Levels;
+
 #[derive(Component)]
 struct GameOverUI;
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    run: Res<CampaignRun>,
+    outcome: Res<RoundOutcome>,
+) {
+    let title = match (outcome.is_versus(), outcome.winner) {
+        (false, _) => "Gameover".to_string(),
+        (true, Some(winner)) => format!("{} wins", outcome.player_name(winner)),
+        (true, None) if outcome.survivors > 0 => "Bots win".to_string(),
+        (true, None) => "Draw".to_string(),
+    };
+
     commands
         .spawn((
             NodeBundle {
@@ -37,26 +58,26 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                     row_gap: Val::Px(40.0),
                     ..default()
                 },
-                background_color: BackgroundColor::from(Color::BLACK),
                 ..default()
             },
+            Themed(Role::Background),
             GameOverUI,
         ))
         .with_children(|children| {
             // text
-            children.spawn(
+            children.spawn((
                 TextBundle::from_section(
                     // Accepts a `String` or any type that converts into a `String`, such as `&str`
-                    "Gameover",
+                    title,
                     TextStyle {
                         font: asset_server.load("fonts/KnightWarrior.otf"),
                         font_size: 60.0,
                         color: Color::WHITE,
-                        ..default()
                     },
                 ) // Set the alignment of the Text
                 .with_text_alignment(TextAlignment::Center),
-            );
+                Themed(Role::Text),
+            ));
 
             // actions
             children
@@ -83,23 +104,57 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                                     align_items: AlignItems::Center,
                                     ..Default::default()
                                 },
-                                background_color: Color::VIOLET.into(),
                                 ..Default::default()
                             },
+                            Themed(Role::ButtonPrimary),
                             PlayAgain,
                         ))
                         .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Play Again",
-                                TextStyle {
-                                    font_size: 24.0,
-                                    font: asset_server.load("fonts/KnightWarrior.otf"),
-                                    color: Color::WHITE,
-                                    ..default()
-                                },
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    "Play Again",
+                                    TextStyle {
+                                        font_size: 24.0,
+                                        font: asset_server.load("fonts/KnightWarrior.otf"),
+                                        color: Color::WHITE,
+                                    },
+                                ),
+                                Themed(Role::Text),
                             ));
                         });
 
+                    // levels
+                    if run.0.is_some() {
+                        children
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(160.0),
+                                        height: Val::Px(40.0),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..Default::default()
+                                    },
+                                    ..Default::default()
+                                },
+                                Themed(Role::ButtonSecondary),
+                                Levels,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    TextBundle::from_section(
+                                        "Levels",
+                                        TextStyle {
+                                            font_size: 24.0,
+                                            font: asset_server.load("fonts/KnightWarrior.otf"),
+                                            color: Color::WHITE,
+                                        },
+                                    ),
+                                    Themed(Role::Text),
+                                ));
+                            });
+                    }
+
                     // quit
                     children
                         .spawn((
@@ -111,20 +166,22 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                                     align_items: AlignItems::Center,
                                     ..Default::default()
                                 },
-                                background_color: Color::ORANGE_RED.into(),
                                 ..Default::default()
                             },
+                            Themed(Role::ButtonDanger),
                             Quit,
                         ))
                         .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Quit",
-                                TextStyle {
-                                    font_size: 24.0,
-                                    font: asset_server.load("fonts/KnightWarrior.otf"),
-                                    color: Color::WHITE,
-                                    ..default()
-                                },
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    "Quit",
+                                    TextStyle {
+                                        font_size: 24.0,
+                                        font: asset_server.load("fonts/KnightWarrior.otf"),
+                                        color: Color::WHITE,
+                                    },
+                                ),
+                                Themed(Role::Text),
                             ));
                         });
                 });
@@ -134,39 +191,27 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
 fn handle_buttons_interaction(
     mut next_state: ResMut<NextState<GameState>>,
     mut app_exit_events: EventWriter<AppExit>,
-    mut interaction_query: Query<
+    interaction_query: Query<
         (
             &Interaction,
-            &mut BackgroundColor,
             Option<&PlayAgain>,
+            Option<&Levels>,
             Option<&Quit>,
         ),
         (Changed<Interaction>, With<Button>),
     >,
 ) {
-    for (interaction, mut color, maybe_btn_play_again, maybe_btn_quit) in &mut interaction_query {
-        match *interaction {
-            Interaction::Pressed => {
-                if let Some(_) = maybe_btn_play_again {
-                    next_state.set(GameState::Game);
-                } else if let Some(_) = maybe_btn_quit {
-                    app_exit_events.send(AppExit);
-                }
-            }
-            Interaction::Hovered => {
-                if let Some(_) = maybe_btn_play_again {
-                    *color = Color::rgba(0.93, 0.51, 0.93, 0.5).into();
-                } else if let Some(_) = maybe_btn_quit {
-                    *color = Color::rgba(1.0, 0.27, 0.0, 0.5).into();
-                }
-            }
-            Interaction::None => {
-                if let Some(_) = maybe_btn_play_again {
-                    *color = Color::VIOLET.into();
-                } else if let Some(_) = maybe_btn_quit {
-                    *color = Color::ORANGE_RED.into();
-                }
-            }
+    for (interaction, maybe_btn_play_again, maybe_btn_levels, maybe_btn_quit) in &interaction_query
+    {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if maybe_btn_play_again.is_some() {
+            next_state.set(GameState::Game);
+        } else if maybe_btn_levels.is_some() {
+            next_state.set(GameState::LevelSelect);
+        } else if maybe_btn_quit.is_some() {
+            app_exit_events.send(AppExit);
         }
     }
 }
@@ -0,0 +1,261 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{Direction, GameOverEvent, Position, RoundCleanup, RoundSetup, Size, SnakeBody},
+    level::{Arena, HazardMotion, HazardSpec},
+    slither::FreePosition,
+    theme::Palette,
+    GameState, RoundState,
+};
+
+/// Hazards move on their own clock, whatever the snake's speed.
+const HAZARD_INTERVAL: Duration = Duration::from_millis(250);
+/// How close a free-moving segment may get to a hazard cell's centre.
+const HAZARD_REACH: f32 = 0.8;
+/// Pulsing blocks stay faintly visible while switched off.
+const INACTIVE_ALPHA: f32 = 0.2;
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HazardTimer::default());
+
+        app.add_systems(
+            OnEnter(GameState::Game),
+            spawn_hazards.in_set(RoundSetup::Spawn),
+        )
+        .add_systems(
+            Update,
+            (
+                move_hazards,
+                update_hazard_cells.run_if(
+                    resource_changed::<HazardTimer>().or_else(resource_changed::<Palette>()),
+                ),
+                hazard_contact,
+            )
+                .chain()
+                .run_if(in_state(RoundState::Playing)),
+        );
+    }
+}
+
+#[derive(Resource, Deref, DerefMut)]
+struct HazardTimer(Timer);
+
+impl Default for HazardTimer {
+    fn default() -> Self {
+        Self(Timer::new(HAZARD_INTERVAL, TimerMode::Repeating))
+    }
+}
+
+#[derive(Component)]
+struct Hazard {
+    spec: HazardSpec,
+    origin: Position,
+    /// Cell entities, in the order of `HazardSpec::cells_at`.
+    cells: Vec<Entity>,
+    active: bool,
+    /// Waypoint being walked to and whether the path is walked forwards.
+    waypoint: usize,
+    forward: bool,
+    /// Current heading of a bouncing hazard.
+    heading: Option<Direction>,
+    /// Hazard ticks since a pulsing block last switched.
+    ticks: u32,
+}
+
+#[derive(Component)]
+struct HazardCell;
+
+fn spawn_hazards(
+    mut commands: Commands,
+    mut timer: ResMut<HazardTimer>,
+    arena: Res<Arena>,
+    palette: Res<Palette>,
+) {
+    *timer = HazardTimer::default();
+
+    for spec in arena.hazards.iter() {
+        let cells = spec
+            .cells_at(spec.origin)
+            .map(|cell| {
+                commands
+                    .spawn((
+                        SpriteBundle {
+                            sprite: Sprite {
+                                color: palette.color(spec.kind.role()),
+                                ..default()
+                            },
+                            // above the floor tiles, below the snake
+                            transform: Transform::from_xyz(0.0, 0.0, 0.5),
+                            ..default()
+                        },
+                        HazardCell,
+                        cell,
+                        Size::square(spec.kind.tile_size()),
+                        RoundCleanup,
+                    ))
+                    .id()
+            })
+            .collect();
+
+        commands.spawn((
+            Hazard {
+                spec: spec.clone(),
+                origin: spec.origin,
+                cells,
+                active: true,
+                waypoint: 0,
+                forward: true,
+                heading: match spec.motion {
+                    HazardMotion::Bounce(direction) => Some(direction),
+                    _ => None,
+                },
+                ticks: 0,
+            },
+            RoundCleanup,
+        ));
+    }
+}
+
+fn move_hazards(
+    time: Res<Time>,
+    arena: Res<Arena>,
+    mut timer: ResMut<HazardTimer>,
+    mut hazard_query: Query<&mut Hazard>,
+) {
+    // only flag the timer when the hazards actually step
+    if !timer
+        .bypass_change_detection()
+        .tick(time.delta())
+        .just_finished()
+    {
+        return;
+    }
+    timer.set_changed();
+
+    for mut hazard in hazard_query.iter_mut() {
+        let hazard = hazard.as_mut();
+        match &hazard.spec.motion {
+            HazardMotion::Still => {}
+            HazardMotion::Path(waypoints) => {
+                if waypoints.is_empty() {
+                    continue;
+                }
+                if hazard.origin == waypoints[hazard.waypoint] {
+                    // walk back the way it came at either end
+                    if waypoints.len() == 1 {
+                        continue;
+                    } else if hazard.forward && hazard.waypoint + 1 == waypoints.len() {
+                        hazard.forward = false;
+                    } else if !hazard.forward && hazard.waypoint == 0 {
+                        hazard.forward = true;
+                    }
+                    if hazard.forward {
+                        hazard.waypoint += 1;
+                    } else {
+                        hazard.waypoint -= 1;
+                    }
+                }
+
+                let target = waypoints[hazard.waypoint];
+                if hazard.origin.x != target.x {
+                    hazard.origin.x += (target.x - hazard.origin.x).signum();
+                } else {
+                    hazard.origin.y += (target.y - hazard.origin.y).signum();
+                }
+            }
+            HazardMotion::Bounce(_) => {
+                let Some(heading) = hazard.heading else {
+                    continue;
+                };
+                let free_step = |direction: Direction| {
+                    let (dx, dy) = direction.offset();
+                    let next = Position {
+                        x: hazard.origin.x + dx,
+                        y: hazard.origin.y + dy,
+                    };
+                    let blocked = hazard
+                        .spec
+                        .cells_at(next)
+                        .any(|cell| arena.is_deadly(&cell));
+                    (!blocked).then_some(next)
+                };
+
+                if let Some(next) = free_step(heading) {
+                    hazard.origin = next;
+                } else if let Some(next) = free_step(heading.opposite()) {
+                    hazard.heading = Some(heading.opposite());
+                    hazard.origin = next;
+                }
+            }
+            HazardMotion::Pulse { on, off } => {
+                hazard.ticks += 1;
+                let limit = if hazard.active { *on } else { *off };
+                if hazard.ticks >= limit {
+                    hazard.active = !hazard.active;
+                    hazard.ticks = 0;
+                }
+            }
+        }
+    }
+}
+
+/// Cells aren't `Themed`: their alpha shows whether the block is on, so the
+/// palette colour is applied here.
+fn update_hazard_cells(
+    palette: Res<Palette>,
+    hazard_query: Query<&Hazard>,
+    mut cell_query: Query<(&mut Position, &mut Sprite), With<HazardCell>>,
+) {
+    for hazard in hazard_query.iter() {
+        for (entity, cell) in hazard.cells.iter().zip(hazard.spec.cells_at(hazard.origin)) {
+            let Ok((mut position, mut sprite)) = cell_query.get_mut(*entity) else {
+                continue;
+            };
+            *position = cell;
+            sprite.color = palette
+                .color(hazard.spec.kind.role())
+                .with_a(if hazard.active { 1.0 } else { INACTIVE_ALPHA });
+        }
+    }
+}
+
+/// Any part of a snake touching an active hazard kills it, in both movement
+/// models.
+fn hazard_contact(
+    snake_query: Query<(Entity, &SnakeBody)>,
+    hazard_query: Query<&Hazard>,
+    segment_query: Query<(Option<&Position>, Option<&FreePosition>), Without<HazardCell>>,
+    mut game_over_ev_writer: EventWriter<GameOverEvent>,
+) {
+    let hazard_cells = hazard_query
+        .iter()
+        .filter(|hazard| hazard.active)
+        .flat_map(|hazard| hazard.spec.cells_at(hazard.origin))
+        .map(|cell| Vec2::new(cell.x as f32, cell.y as f32))
+        .collect::<Vec<Vec2>>();
+    if hazard_cells.is_empty() {
+        return;
+    }
+
+    for (snake, body) in snake_query.iter() {
+        let touched = body.iter().any(|segment| {
+            let position = match segment_query.get(*segment) {
+                Ok((Some(position), _)) => Vec2::new(position.x as f32, position.y as f32),
+                Ok((None, Some(position))) => position.0,
+                _ => return false,
+            };
+            hazard_cells
+                .iter()
+                .any(|cell| (*cell - position).abs().max_element() < HAZARD_REACH)
+        });
+
+        if touched {
+            game_over_ev_writer.send(GameOverEvent(snake));
+        }
+    }
+}
@@ -0,0 +1,406 @@
+//! Hex grid mode: the snake crosses a hexagon of hexagonal cells, turning left
+//! or right between six directions. Cells use axial coordinates, `q` growing
+//! to the east and `r` to the south-east, laid out pointy-top.
+
+use bevy::{
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    window::PrimaryWindow,
+};
+use rand::{seq::SliceRandom, thread_rng};
+
+use crate::{
+    game::{
+        movement_tick, Food, GameOverEvent, GrowthEvent, Player, RoundCleanup, RoundSetup,
+        SnakeBody,
+    },
+    hud::HUD_HEIGHT,
+    level::{CurrentLevel, Level},
+    theme::{Palette, Role},
+    GameMode, GameState, RoundState,
+};
+
+/// Cells from the centre to the edge of the board.
+const HEX_RADIUS: i32 = 6;
+const SQRT_3: f32 = 1.732_050_8;
+
+pub struct HexPlugin;
+
+impl Plugin for HexPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Game),
+            setup.in_set(RoundSetup::Spawn).run_if(hex_round),
+        );
+
+        app.add_systems(
+            Update,
+            hex_input
+                .before(hex_movement)
+                .run_if(in_state(RoundState::Countdown).or_else(in_state(RoundState::Playing)))
+                .run_if(hex_round),
+        )
+        .add_systems(
+            Update,
+            (
+                hex_movement.run_if(movement_tick),
+                hex_eating,
+                hex_growth,
+                spawn_food,
+            )
+                .chain()
+                .run_if(in_state(RoundState::Playing))
+                .run_if(hex_round),
+        )
+        .add_systems(PostUpdate, hex_translation)
+        .add_systems(
+            Update,
+            recolor
+                .run_if(resource_changed::<Palette>())
+                .run_if(resource_exists::<HexAssets>()),
+        );
+    }
+}
+
+/// Placeholder square arena; only its rules, such as the speed, apply.
+pub(crate) fn arena() -> Level {
+    Level {
+        name: "Hex".to_string(),
+        ..Level::default()
+    }
+}
+
+/// Levels picked from the campaign or the editor are square and keep the
+/// square grid.
+pub(crate) fn hex_round(mode: Res<GameMode>, current_level: Res<CurrentLevel>) -> bool {
+    *mode == GameMode::Hex && current_level.0.is_none()
+}
+
+/// Axial coordinates of a hex cell.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct HexPosition {
+    q: i32,
+    r: i32,
+}
+
+impl HexPosition {
+    fn neighbour(&self, direction: HexDirection) -> Self {
+        let (dq, dr) = direction.offset();
+        Self {
+            q: self.q + dq,
+            r: self.r + dr,
+        }
+    }
+
+    fn in_arena(&self) -> bool {
+        self.q.abs().max(self.r.abs()).max((self.q + self.r).abs()) <= HEX_RADIUS
+    }
+}
+
+/// The six directions, counter-clockwise from east.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HexDirection {
+    East,
+    NorthEast,
+    NorthWest,
+    West,
+    SouthWest,
+    SouthEast,
+}
+
+impl HexDirection {
+    const ALL: [Self; 6] = [
+        Self::East,
+        Self::NorthEast,
+        Self::NorthWest,
+        Self::West,
+        Self::SouthWest,
+        Self::SouthEast,
+    ];
+
+    fn offset(&self) -> (i32, i32) {
+        match self {
+            Self::East => (1, 0),
+            Self::NorthEast => (1, -1),
+            Self::NorthWest => (0, -1),
+            Self::West => (-1, 0),
+            Self::SouthWest => (-1, 1),
+            Self::SouthEast => (0, 1),
+        }
+    }
+
+    fn turn(&self, steps: usize) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|direction| direction == self)
+            .unwrap_or(0);
+        Self::ALL[(index + steps) % Self::ALL.len()]
+    }
+
+    fn turn_left(&self) -> Self {
+        self.turn(1)
+    }
+
+    fn turn_right(&self) -> Self {
+        self.turn(Self::ALL.len() - 1)
+    }
+}
+
+#[derive(Component)]
+struct HexHead {
+    direction: HexDirection,
+    queued: Option<HexDirection>,
+    tail: Option<HexPosition>,
+}
+
+/// Share of a full cell an entity covers, like `Size` on the square grid.
+#[derive(Component)]
+struct HexScale(f32);
+
+#[derive(Resource)]
+struct HexAssets {
+    mesh: Mesh2dHandle,
+    tile: Handle<ColorMaterial>,
+    head: Handle<ColorMaterial>,
+    body: Handle<ColorMaterial>,
+    food: Handle<ColorMaterial>,
+}
+
+fn cells() -> impl Iterator<Item = HexPosition> {
+    (-HEX_RADIUS..=HEX_RADIUS)
+        .flat_map(|q| (-HEX_RADIUS..=HEX_RADIUS).map(move |r| HexPosition { q, r }))
+        .filter(HexPosition::in_arena)
+}
+
+/// Centre-to-corner size of a cell that fits the whole board under the HUD.
+fn hex_size(window: &Window) -> f32 {
+    let across = (2 * HEX_RADIUS + 1) as f32;
+    let width = window.width() / (SQRT_3 * across);
+    let height = (window.height() - HUD_HEIGHT) / (1.5 * (across - 1.0) + 2.0);
+    width.min(height)
+}
+
+fn hex_to_world(pos: &HexPosition, window: &Window) -> Vec2 {
+    let size = hex_size(window);
+    Vec2::new(
+        size * SQRT_3 * (pos.q as f32 + pos.r as f32 / 2.0),
+        // the board sits below the HUD bar
+        -size * 1.5 * pos.r as f32 - HUD_HEIGHT / 2.0,
+    )
+}
+
+fn setup(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    // pointy-top, like the layout
+    let mesh: Mesh2dHandle = meshes.add(shape::RegularPolygon::new(1.0, 6).into()).into();
+    let assets = HexAssets {
+        mesh: mesh.clone(),
+        tile: materials.add(palette.color(Role::Tile).into()),
+        head: materials.add(palette.color(Role::SnakeHead).into()),
+        body: materials.add(palette.color(Role::SnakeBody).into()),
+        food: materials.add(palette.color(Role::Food).into()),
+    };
+
+    for cell in cells() {
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: mesh.clone(),
+                material: assets.tile.clone(),
+                transform: Transform::from_xyz(0.0, 0.0, -1.0),
+                ..default()
+            },
+            cell,
+            HexScale(0.95),
+            RoundCleanup,
+        ));
+    }
+
+    let head = commands
+        .spawn((
+            MaterialMesh2dBundle {
+                mesh,
+                material: assets.head.clone(),
+                transform: Transform::from_xyz(0.0, 0.0, 0.2),
+                ..default()
+            },
+            HexHead {
+                direction: HexDirection::East,
+                queued: None,
+                tail: None,
+            },
+            Player(0),
+            HexPosition { q: 0, r: 0 },
+            HexScale(0.8),
+            RoundCleanup,
+        ))
+        .id();
+    let neck = spawn_body(&mut commands, &assets, HexPosition { q: -1, r: 0 });
+    commands.entity(head).insert(SnakeBody(vec![head, neck]));
+
+    commands.insert_resource(assets);
+}
+
+fn spawn_body(commands: &mut Commands, assets: &HexAssets, position: HexPosition) -> Entity {
+    commands
+        .spawn((
+            MaterialMesh2dBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.body.clone(),
+                transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                ..default()
+            },
+            position,
+            HexScale(0.6),
+            RoundCleanup,
+        ))
+        .id()
+}
+
+fn hex_input(keyboard_input: Res<Input<KeyCode>>, mut head_query: Query<&mut HexHead>) {
+    for mut head in head_query.iter_mut() {
+        if head.queued.is_some() {
+            continue;
+        }
+
+        if keyboard_input.any_just_pressed([KeyCode::Left, KeyCode::A]) {
+            head.queued = Some(head.direction.turn_left());
+        } else if keyboard_input.any_just_pressed([KeyCode::Right, KeyCode::D]) {
+            head.queued = Some(head.direction.turn_right());
+        }
+    }
+}
+
+fn hex_movement(
+    mut head_query: Query<(Entity, &mut HexHead, &SnakeBody)>,
+    mut positions_query: Query<&mut HexPosition>,
+    mut game_over_ev_writer: EventWriter<GameOverEvent>,
+) {
+    let Ok((entity, mut head, body)) = head_query.get_single_mut() else {
+        return;
+    };
+    let body_positions = body
+        .iter()
+        .map(|segment| *positions_query.get(*segment).unwrap())
+        .collect::<Vec<HexPosition>>();
+
+    if let Some(queued) = head.queued.take() {
+        head.direction = queued;
+    }
+
+    let head_pos = body_positions[0].neighbour(head.direction);
+    if !head_pos.in_arena() || body_positions.contains(&head_pos) {
+        game_over_ev_writer.send(GameOverEvent(entity));
+    }
+
+    for (segment, pos) in body
+        .iter()
+        .zip(std::iter::once(head_pos).chain(body_positions.iter().copied()))
+    {
+        *positions_query.get_mut(*segment).unwrap() = pos;
+    }
+    head.tail = body_positions.last().copied();
+}
+
+fn hex_eating(
+    mut commands: Commands,
+    head_query: Query<(Entity, &HexPosition), With<HexHead>>,
+    food_query: Query<(Entity, &HexPosition), With<Food>>,
+    mut growth_ev_writer: EventWriter<GrowthEvent>,
+) {
+    for (head, head_pos) in head_query.iter() {
+        for (food, food_pos) in food_query.iter() {
+            if head_pos == food_pos {
+                commands.entity(food).despawn();
+                growth_ev_writer.send(GrowthEvent(head));
+            }
+        }
+    }
+}
+
+fn hex_growth(
+    mut commands: Commands,
+    assets: Res<HexAssets>,
+    mut head_query: Query<(&HexHead, &mut SnakeBody)>,
+    mut growth_ev_reader: EventReader<GrowthEvent>,
+) {
+    for GrowthEvent(snake) in growth_ev_reader.read() {
+        let Ok((head, mut body)) = head_query.get_mut(*snake) else {
+            continue;
+        };
+        if let Some(tail) = head.tail {
+            body.push(spawn_body(&mut commands, &assets, tail));
+        }
+    }
+}
+
+fn spawn_food(
+    mut commands: Commands,
+    assets: Res<HexAssets>,
+    food_query: Query<(), With<Food>>,
+    body_query: Query<&SnakeBody>,
+    positions_query: Query<&HexPosition>,
+) {
+    if !food_query.is_empty() {
+        return;
+    }
+
+    let occupied = body_query
+        .iter()
+        .flat_map(|body| body.iter())
+        .filter_map(|segment| positions_query.get(*segment).ok().copied())
+        .collect::<Vec<HexPosition>>();
+    let candidates = cells()
+        .filter(|cell| !occupied.contains(cell))
+        .collect::<Vec<HexPosition>>();
+    let Some(food_pos) = candidates.choose(&mut thread_rng()).copied() else {
+        return;
+    };
+
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: assets.mesh.clone(),
+            material: assets.food.clone(),
+            ..default()
+        },
+        Food,
+        food_pos,
+        HexScale(0.7),
+        RoundCleanup,
+    ));
+}
+
+fn recolor(
+    palette: Res<Palette>,
+    assets: Res<HexAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (material, role) in [
+        (&assets.tile, Role::Tile),
+        (&assets.head, Role::SnakeHead),
+        (&assets.body, Role::SnakeBody),
+        (&assets.food, Role::Food),
+    ] {
+        if let Some(material) = materials.get_mut(material) {
+            material.color = palette.color(role);
+        }
+    }
+}
+
+fn hex_translation(
+    primary_query: Query<&Window, With<PrimaryWindow>>,
+    mut query: Query<(&HexPosition, &HexScale, &mut Transform)>,
+) {
+    let Ok(window) = primary_query.get_single() else {
+        return;
+    };
+    let size = hex_size(window);
+
+    for (position, scale, mut transform) in query.iter_mut() {
+        transform.translation = hex_to_world(position, window).extend(transform.translation.z);
+        transform.scale = Vec3::new(size * scale.0, size * scale.0, 1.0);
+    }
+}
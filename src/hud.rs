@@ -0,0 +1,193 @@
+use bevy::prelude::*;
+
+use crate::{
+    game::{HighScore, Player, RoundTime, SnakeBody, SpeedLevel},
+    scoring::ScoreBoard,
+    theme::{Palette, Role, Themed},
+    GameState,
+};
+
+/// Height of the top bar in logical pixels; the arena is laid out below it.
+pub(crate) const HUD_HEIGHT: f32 = 40.0;
+
+const HUD_FONT_SIZE: f32 = 18.0;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Game), setup_hud);
+        app.add_systems(
+            Update,
+            (
+                update_score.run_if(resource_changed::<ScoreBoard>()),
+                update_high_score.run_if(resource_changed::<HighScore>()),
+                update_length,
+                update_speed_level.run_if(resource_changed::<SpeedLevel>()),
+                update_elapsed_time.run_if(resource_changed::<RoundTime>()),
+                paint_widgets.run_if(resource_changed::<Palette>()),
+            )
+                .run_if(in_state(GameState::Game)),
+        );
+        app.add_systems(OnExit(GameState::Game), cleanup_hud);
+    }
+}
+
+#[derive(Component)]
+struct Hud;
+
+/// A label and a value, each in its own text colour.
+#[derive(Component)]
+struct Widget;
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct HighScoreText;
+
+#[derive(Component)]
+struct LengthText;
+
+#[derive(Component)]
+struct SpeedLevelText;
+
+#[derive(Component)]
+struct ElapsedTimeText;
+
+/// Active power-ups, "-" while the game has none.
+#[derive(Component)]
+struct PowerUpsText;
+
+fn format_elapsed_time(round_time: &RoundTime) -> String {
+    let secs = round_time.elapsed().as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+fn setup_hud(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    palette: Res<Palette>,
+    scoreboard: Res<ScoreBoard>,
+    high_score: Res<HighScore>,
+    speed_level: Res<SpeedLevel>,
+    round_time: Res<RoundTime>,
+) {
+    let font = asset_server.load("fonts/KnightWarrior.otf");
+    let widget = |label: &str, value: String| {
+        let text = TextBundle::from_sections([
+            TextSection::new(
+                label,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: HUD_FONT_SIZE,
+                    color: palette.color(Role::TextMuted),
+                },
+            ),
+            TextSection::new(
+                value,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: HUD_FONT_SIZE,
+                    color: palette.color(Role::TextAccent),
+                },
+            ),
+        ]);
+        (text, Widget)
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.0),
+                    width: Val::Percent(100.0),
+                    height: Val::Px(HUD_HEIGHT),
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::SpaceBetween,
+                    padding: UiRect::horizontal(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            Themed(Role::Panel),
+            Hud,
+        ))
+        .with_children(|children| {
+            children.spawn((widget("Score ", format_score(&scoreboard)), ScoreText));
+            children.spawn((widget("Best ", high_score.0.to_string()), HighScoreText));
+            children.spawn((widget("Length ", "0".to_string()), LengthText));
+            children.spawn((widget("Lv ", speed_level.0.to_string()), SpeedLevelText));
+            children.spawn((
+                widget("Time ", format_elapsed_time(&round_time)),
+                ElapsedTimeText,
+            ));
+            children.spawn((widget("", "-".to_string()), PowerUpsText));
+        });
+}
+
+fn update_score(scoreboard: Res<ScoreBoard>, mut query: Query<&mut Text, With<ScoreText>>) {
+    if let Ok(mut text) = query.get_single_mut() {
+        text.sections[1].value = format_score(&scoreboard);
+    }
+}
+
+/// The total, followed by the combo multiplier while it is up.
+fn format_score(scoreboard: &ScoreBoard) -> String {
+    match scoreboard.multiplier {
+        1 => scoreboard.total().to_string(),
+        multiplier => format!("{} x{multiplier}", scoreboard.total()),
+    }
+}
+
+fn update_high_score(high_score: Res<HighScore>, mut query: Query<&mut Text, With<HighScoreText>>) {
+    if let Ok(mut text) = query.get_single_mut() {
+        text.sections[1].value = high_score.0.to_string();
+    }
+}
+
+/// Length of the first player's snake.
+fn update_length(
+    body_query: Query<(&Player, &SnakeBody), Changed<SnakeBody>>,
+    mut query: Query<&mut Text, With<LengthText>>,
+) {
+    let Some((_, body)) = body_query.iter().find(|(player, _)| player.0 == 0) else {
+        return;
+    };
+    if let Ok(mut text) = query.get_single_mut() {
+        text.sections[1].value = body.len().to_string();
+    }
+}
+
+fn update_speed_level(
+    speed_level: Res<SpeedLevel>,
+    mut query: Query<&mut Text, With<SpeedLevelText>>,
+) {
+    if let Ok(mut text) = query.get_single_mut() {
+        text.sections[1].value = speed_level.0.to_string();
+    }
+}
+
+fn update_elapsed_time(
+    round_time: Res<RoundTime>,
+    mut query: Query<&mut Text, With<ElapsedTimeText>>,
+) {
+    if let Ok(mut text) = query.get_single_mut() {
+        text.sections[1].value = format_elapsed_time(&round_time);
+    }
+}
+
+fn paint_widgets(palette: Res<Palette>, mut query: Query<&mut Text, With<Widget>>) {
+    for mut text in query.iter_mut() {
+        text.sections[0].style.color = palette.color(Role::TextMuted);
+        text.sections[1].style.color = palette.color(Role::TextAccent);
+    }
+}
+
+fn cleanup_hud(mut commands: Commands, hud: Query<Entity, With<Hud>>) {
+    for entity in hud.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,994 @@
+//! Text-based level format.
+//!
+//! A level file starts with a `key: value` header, followed by a `---` line and
+//! the arena drawn as an ASCII grid, top row first:
+//!
+//! ```text
+//! name: Boxed In
+//! size: 12x8
+//! facing: up
+//! speed: 300
+//! food: spots
+//! ---
+//! ############
+//! #....F.....#
+//! #..........#
+//! #....S.....#
+//! ...
+//! ```
+//!
+//! Grid cells are `.` (floor), `#` (wall), `S` (snake spawn, heading in the
+//! `facing` direction), `F` (fixed food spot) and the digits `0`-`9`, each of
+//! which must appear exactly twice to form a pair of portals. `speed` is the
+//! base movement interval in milliseconds and `food` is either `random` or
+//! `spots`.
+//!
+//! An optional second `---` line starts the hazard list, one per line:
+//!
+//! ```text
+//! ---
+//! saw 2,3 path 2,3 9,3
+//! slider 1,6 1x3 bounce right
+//! pulse 5,5 2x2 on 4 off 4
+//! ```
+//!
+//! Each hazard is a kind (`saw`, `slider` or `pulse`), the column and row of
+//! its top-left cell counted from the top-left of the grid, an optional block
+//! size and how it moves: along `path` waypoints and back, with `bounce` off
+//! walls, or switching `on` and `off` for a number of hazard ticks.
+//!
+//! Campaign levels also set a `goal` (`length 8`, `eat 10` or `survive 60`)
+//! and a `par` used for star ratings: seconds to reach a length or eat goal,
+//! food eaten for a survive goal.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt, iter,
+    time::Duration,
+};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use bevy_asset_loader::asset_collection::AssetCollection;
+
+use crate::{
+    campaign::CampaignRun,
+    game::{Direction, Position, RoundCleanup, RoundSetup, Size},
+    hex, maze, royale,
+    theme::{Role, Themed},
+    tron, GameMode, GameSeed, GameState,
+};
+
+/// Both ends of a pair share a colour, pairs cycle through the list.
+pub(crate) const PORTAL_COLORS: [Color; 5] = [
+    Color::CYAN,
+    Color::FUCHSIA,
+    Color::GOLD,
+    Color::TOMATO,
+    Color::AQUAMARINE,
+];
+const DEFAULT_MOVEMENT_INTERVAL: Duration = Duration::from_millis(350);
+/// Portal pairs are written as single digits.
+pub(crate) const MAX_PORTAL_PAIRS: usize = 10;
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .insert_resource(Arena::default())
+            .insert_resource(CurrentLevel::default());
+
+        app.add_systems(
+            OnEnter(GameState::Game),
+            build_arena.in_set(RoundSetup::Reset),
+        );
+    }
+}
+
+#[derive(AssetCollection, Resource)]
+pub(crate) struct LevelAssets {
+    #[asset(path = "levels/classic.level")]
+    pub(crate) classic: Handle<Level>,
+    /// Campaign levels, in the order they are unlocked.
+    #[asset(
+        paths(
+            "levels/campaign/01-warm-up.level",
+            "levels/campaign/02-pillars.level",
+            "levels/campaign/03-corridors.level",
+            "levels/campaign/04-pantry.level",
+            "levels/campaign/05-the-box.level",
+            "levels/campaign/06-warp.level",
+            "levels/campaign/07-sawmill.level",
+            "levels/campaign/08-expanse.level",
+        ),
+        collection(typed)
+    )]
+    pub(crate) campaign: Vec<Handle<Level>>,
+}
+
+/// Level played by the next round. When unset, the Maze mode generates one and
+/// every other mode plays the classic level.
+#[derive(Resource, Default)]
+pub(crate) struct CurrentLevel(pub(crate) Option<Handle<Level>>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FoodPlacement {
+    /// Food appears on any free cell.
+    Random,
+    /// Food only appears on the level's `F` cells.
+    Spots,
+}
+
+/// What has to be done to complete a campaign level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Goal {
+    Length(usize),
+    Eat(usize),
+    Survive(Duration),
+}
+
+impl Goal {
+    pub(crate) fn label(&self) -> String {
+        match self {
+            Self::Length(length) => format!("Reach length {length}"),
+            Self::Eat(food) => format!("Eat {food} food"),
+            Self::Survive(time) => format!("Survive {}s", time.as_secs()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LevelRules {
+    pub(crate) movement_interval: Duration,
+    pub(crate) food: FoodPlacement,
+    pub(crate) goal: Option<Goal>,
+    pub(crate) par: Option<u32>,
+}
+
+impl Default for LevelRules {
+    fn default() -> Self {
+        Self {
+            movement_interval: DEFAULT_MOVEMENT_INTERVAL,
+            food: FoodPlacement::Random,
+            goal: None,
+            par: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HazardKind {
+    Saw,
+    Slider,
+    Pulse,
+}
+
+impl HazardKind {
+    pub(crate) fn role(&self) -> Role {
+        match self {
+            Self::Saw => Role::HazardSaw,
+            Self::Slider => Role::HazardSlider,
+            Self::Pulse => Role::HazardPulse,
+        }
+    }
+
+    /// Saws are drawn smaller than the cell, blocks fill it.
+    pub(crate) fn tile_size(&self) -> f32 {
+        match self {
+            Self::Saw => 0.8,
+            Self::Slider | Self::Pulse => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum HazardMotion {
+    Still,
+    /// Walks to each waypoint in turn, then back the same way.
+    Path(Vec<Position>),
+    /// Goes straight and turns around at walls and the arena edge.
+    Bounce(Direction),
+    /// Switches on and off, counted in hazard ticks.
+    Pulse {
+        on: u32,
+        off: u32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct HazardSpec {
+    pub(crate) kind: HazardKind,
+    /// Bottom-left cell of the block, waypoints are given for this cell too.
+    pub(crate) origin: Position,
+    pub(crate) width: i32,
+    pub(crate) height: i32,
+    pub(crate) motion: HazardMotion,
+}
+
+impl HazardSpec {
+    /// Cells covered when the block sits at `origin`.
+    pub(crate) fn cells_at(&self, origin: Position) -> impl Iterator<Item = Position> + '_ {
+        (0..self.width).flat_map(move |x| {
+            (0..self.height).map(move |y| Position {
+                x: origin.x + x,
+                y: origin.y + y,
+            })
+        })
+    }
+
+    /// Whether the block stays on the level at its origin and every waypoint,
+    /// and so all along its path, which runs straight between them.
+    pub(crate) fn fits_in(&self, level: &Level) -> bool {
+        let waypoints = match &self.motion {
+            HazardMotion::Path(waypoints) => waypoints.as_slice(),
+            _ => &[],
+        };
+        iter::once(&self.origin)
+            .chain(waypoints)
+            .all(|corner| self.cells_at(*corner).all(|cell| level.contains(&cell)))
+    }
+}
+
+fn parse_direction(value: &str) -> Option<Direction> {
+    match value {
+        "up" => Some(Direction::Up),
+        "down" => Some(Direction::Down),
+        "left" => Some(Direction::Left),
+        "right" => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Up => "up",
+        Direction::Down => "down",
+        Direction::Left => "left",
+        Direction::Right => "right",
+    }
+}
+
+#[derive(Asset, TypePath, Debug, Clone, PartialEq)]
+pub(crate) struct Level {
+    pub(crate) name: String,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) rules: LevelRules,
+    pub(crate) spawn: Position,
+    pub(crate) facing: Direction,
+    pub(crate) walls: HashSet<Position>,
+    pub(crate) food_spots: Vec<Position>,
+    pub(crate) portals: Vec<[Position; 2]>,
+    pub(crate) hazards: Vec<HazardSpec>,
+}
+
+impl Default for Level {
+    /// An empty 10x10 arena with the snake in the middle.
+    fn default() -> Self {
+        Self {
+            name: "Classic".to_string(),
+            width: 10,
+            height: 10,
+            rules: LevelRules::default(),
+            spawn: Position { x: 5, y: 5 },
+            facing: Direction::Up,
+            walls: HashSet::new(),
+            food_spots: Vec::new(),
+            portals: Vec::new(),
+            hazards: Vec::new(),
+        }
+    }
+}
+
+impl Level {
+    pub(crate) fn contains(&self, pos: &Position) -> bool {
+        pos.x >= 0 && pos.y >= 0 && (pos.x as u32) < self.width && (pos.y as u32) < self.height
+    }
+
+    pub(crate) fn is_wall(&self, pos: &Position) -> bool {
+        self.walls.contains(pos)
+    }
+
+    /// Whether the snake dies when its head reaches `pos`.
+    pub(crate) fn is_deadly(&self, pos: &Position) -> bool {
+        !self.contains(pos) || self.is_wall(pos)
+    }
+
+    /// The other end of the portal on `pos`, if there is one.
+    pub(crate) fn portal_exit(&self, pos: &Position) -> Option<Position> {
+        self.portals.iter().find_map(|[first, second]| {
+            if first == pos {
+                Some(*second)
+            } else if second == pos {
+                Some(*first)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Cell reached by moving one step from `pos`. Stepping onto a portal comes
+    /// out on its partner, so the result is not always adjacent to `pos`.
+    pub(crate) fn step(&self, pos: &Position, direction: Direction) -> Position {
+        let (dx, dy) = direction.offset();
+        let next = Position {
+            x: pos.x + dx,
+            y: pos.y + dy,
+        };
+        self.portal_exit(&next).unwrap_or(next)
+    }
+
+    /// Where each snake starts and which way it faces. A lone snake uses the
+    /// level's spawn; several snakes are spread around the arena, each on the
+    /// free cell nearest its corner of the spread with room for its neck.
+    pub(crate) fn spawn_points(&self, count: usize) -> Vec<(Position, Direction)> {
+        if count <= 1 {
+            return vec![(self.spawn, self.facing)];
+        }
+
+        let (width, height) = (self.width as i32, self.height as i32);
+        // around the centre, everyone heading the same way round
+        let targets = [
+            (
+                Position {
+                    x: width / 4,
+                    y: height / 2,
+                },
+                Direction::Up,
+            ),
+            (
+                Position {
+                    x: width - 1 - width / 4,
+                    y: height / 2,
+                },
+                Direction::Down,
+            ),
+            (
+                Position {
+                    x: width / 2,
+                    y: height / 4,
+                },
+                Direction::Left,
+            ),
+            (
+                Position {
+                    x: width / 2,
+                    y: height - 1 - height / 4,
+                },
+                Direction::Right,
+            ),
+        ];
+
+        let mut taken = HashSet::new();
+        let mut points = Vec::with_capacity(count);
+        for (target, facing) in targets.into_iter().cycle().take(count) {
+            let (dx, dy) = facing.offset();
+            let neck = |head: Position| Position {
+                x: head.x - dx,
+                y: head.y - dy,
+            };
+            let fits = |cell: &Position| {
+                !self.is_deadly(cell) && self.portal_exit(cell).is_none() && !taken.contains(cell)
+            };
+
+            let head = (0..width.max(height))
+                .flat_map(|distance| {
+                    (-distance..=distance).flat_map(move |x| {
+                        (-distance..=distance)
+                            .filter(move |y| x.abs().max(y.abs()) == distance)
+                            .map(move |y| Position {
+                                x: target.x + x,
+                                y: target.y + y,
+                            })
+                    })
+                })
+                .find(|head| fits(head) && fits(&neck(*head)))
+                .unwrap_or(self.spawn);
+
+            taken.insert(head);
+            taken.insert(neck(head));
+            points.push((head, facing));
+        }
+        points
+    }
+
+    /// `col,row` of a block's top-left cell, as written in level files, to its
+    /// bottom-left cell.
+    fn parse_cell(&self, value: &str, block_height: i32) -> Option<Position> {
+        let (col, row) = value.split_once(',')?;
+        let (col, row) = (
+            col.trim().parse::<i32>().ok()?,
+            row.trim().parse::<i32>().ok()?,
+        );
+        Some(Position {
+            x: col,
+            y: self.height as i32 - row - block_height,
+        })
+    }
+
+    fn write_cell(&self, pos: &Position, block_height: i32) -> String {
+        format!("{},{}", pos.x, self.height as i32 - pos.y - block_height)
+    }
+
+    fn parse_hazard(&self, line: &str) -> Option<HazardSpec> {
+        let mut tokens = line.split_whitespace().peekable();
+        let kind = match tokens.next()? {
+            "saw" => HazardKind::Saw,
+            "slider" => HazardKind::Slider,
+            "pulse" => HazardKind::Pulse,
+            _ => return None,
+        };
+        let corner = tokens.next()?;
+        let (width, height) = match tokens.peek().and_then(|size| size.split_once('x')) {
+            Some((width, height)) => {
+                let size = (width.parse().ok()?, height.parse().ok()?);
+                tokens.next();
+                size
+            }
+            None => (1, 1),
+        };
+        if width < 1 || height < 1 {
+            return None;
+        }
+        let origin = self.parse_cell(corner, height)?;
+
+        let motion = match tokens.next() {
+            None => HazardMotion::Still,
+            Some("path") => HazardMotion::Path(
+                tokens
+                    .by_ref()
+                    .map(|waypoint| self.parse_cell(waypoint, height))
+                    .collect::<Option<Vec<Position>>>()?,
+            ),
+            Some("bounce") => HazardMotion::Bounce(parse_direction(tokens.next()?)?),
+            Some("on") => {
+                let on = tokens.next()?.parse().ok()?;
+                if tokens.next()? != "off" {
+                    return None;
+                }
+                let off = tokens.next()?.parse().ok()?;
+                HazardMotion::Pulse { on, off }
+            }
+            Some(_) => return None,
+        };
+        if tokens.next().is_some() {
+            return None;
+        }
+
+        Some(HazardSpec {
+            kind,
+            origin,
+            width,
+            height,
+            motion,
+        })
+        .filter(|hazard| hazard.fits_in(self))
+    }
+
+    pub(crate) fn parse(source: &str) -> Result<Self, LevelError> {
+        let mut level = Level::default();
+        let mut size = None;
+        let mut lines = source.lines().enumerate();
+
+        // header
+        loop {
+            let Some((number, line)) = lines.next() else {
+                return Err(LevelError::MissingGrid);
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line == "---" {
+                break;
+            }
+
+            let (key, value) = line
+                .split_once(':')
+                .ok_or(LevelError::InvalidHeader(number + 1))?;
+            let value = value.trim();
+            match key.trim() {
+                "name" => level.name = value.to_string(),
+                "size" => {
+                    let (width, height) = value
+                        .split_once('x')
+                        .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+                        .filter(|(w, h)| *w > 0 && *h > 0)
+                        .ok_or(LevelError::InvalidHeader(number + 1))?;
+                    size = Some((width, height));
+                }
+                "facing" => {
+                    level.facing =
+                        parse_direction(value).ok_or(LevelError::InvalidHeader(number + 1))?;
+                }
+                "speed" => {
+                    let millis = value
+                        .parse()
+                        .map_err(|_| LevelError::InvalidHeader(number + 1))?;
+                    level.rules.movement_interval = Duration::from_millis(millis);
+                }
+                "food" => {
+                    level.rules.food = match value {
+                        "random" => FoodPlacement::Random,
+                        "spots" => FoodPlacement::Spots,
+                        _ => return Err(LevelError::InvalidHeader(number + 1)),
+                    }
+                }
+                "goal" => {
+                    let goal = value
+                        .split_once(' ')
+                        .and_then(|(kind, amount)| {
+                            let amount = amount.trim().parse().ok()?;
+                            match kind {
+                                "length" => Some(Goal::Length(amount)),
+                                "eat" => Some(Goal::Eat(amount)),
+                                "survive" => {
+                                    Some(Goal::Survive(Duration::from_secs(amount as u64)))
+                                }
+                                _ => None,
+                            }
+                        })
+                        .ok_or(LevelError::InvalidHeader(number + 1))?;
+                    level.rules.goal = Some(goal);
+                }
+                "par" => {
+                    let par = value
+                        .parse()
+                        .map_err(|_| LevelError::InvalidHeader(number + 1))?;
+                    level.rules.par = Some(par);
+                }
+                _ => return Err(LevelError::InvalidHeader(number + 1)),
+            }
+        }
+
+        if level.rules.goal.is_some() && level.rules.par.is_none() {
+            return Err(LevelError::MissingPar);
+        }
+
+        let (width, height) = size.ok_or(LevelError::MissingSize)?;
+        level.width = width;
+        level.height = height;
+
+        // grid, top row first, up to the hazard list
+        let rows = lines
+            .by_ref()
+            .map(|(number, line)| (number, line.trim_end_matches('\r')))
+            .filter(|(_, line)| !line.is_empty())
+            .take_while(|(_, line)| line.trim() != "---")
+            .collect::<Vec<_>>();
+        if rows.len() != height as usize {
+            return Err(LevelError::SizeMismatch);
+        }
+
+        let mut spawn = None;
+        let mut portals = BTreeMap::<char, Vec<Position>>::new();
+        for (row, (number, line)) in rows.iter().enumerate() {
+            if line.chars().count() != width as usize {
+                return Err(LevelError::SizeMismatch);
+            }
+
+            for (x, cell) in line.chars().enumerate() {
+                let pos = Position {
+                    x: x as i32,
+                    y: (height as usize - 1 - row) as i32,
+                };
+                match cell {
+                    '.' | ' ' => {}
+                    '#' => {
+                        level.walls.insert(pos);
+                    }
+                    'F' => level.food_spots.push(pos),
+                    '0'..='9' => portals.entry(cell).or_default().push(pos),
+                    'S' => {
+                        if spawn.replace(pos).is_some() {
+                            return Err(LevelError::InvalidCell(number + 1, cell));
+                        }
+                    }
+                    _ => return Err(LevelError::InvalidCell(number + 1, cell)),
+                }
+            }
+        }
+        level.spawn = spawn.ok_or(LevelError::MissingSpawn)?;
+
+        for (digit, ends) in portals {
+            match ends[..] {
+                [first, second] => level.portals.push([first, second]),
+                _ => return Err(LevelError::UnpairedPortal(digit)),
+            }
+        }
+
+        for (number, line) in lines {
+            let line = line.trim();
+            if !line.is_empty() {
+                let hazard = level
+                    .parse_hazard(line)
+                    .ok_or(LevelError::InvalidHazard(number + 1))?;
+                level.hazards.push(hazard);
+            }
+        }
+
+        if level.rules.food == FoodPlacement::Spots && level.food_spots.is_empty() {
+            return Err(LevelError::MissingFoodSpots);
+        }
+
+        Ok(level)
+    }
+}
+
+/// Writes the level back in the format read by [`Level::parse`].
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let food = match self.rules.food {
+            FoodPlacement::Random => "random",
+            FoodPlacement::Spots => "spots",
+        };
+
+        writeln!(f, "name: {}", self.name)?;
+        writeln!(f, "size: {}x{}", self.width, self.height)?;
+        writeln!(f, "facing: {}", direction_name(self.facing))?;
+        writeln!(f, "speed: {}", self.rules.movement_interval.as_millis())?;
+        writeln!(f, "food: {food}")?;
+        match self.rules.goal {
+            Some(Goal::Length(length)) => writeln!(f, "goal: length {length}")?,
+            Some(Goal::Eat(food)) => writeln!(f, "goal: eat {food}")?,
+            Some(Goal::Survive(time)) => writeln!(f, "goal: survive {}", time.as_secs())?,
+            None => {}
+        }
+        if let Some(par) = self.rules.par {
+            writeln!(f, "par: {par}")?;
+        }
+        writeln!(f, "---")?;
+
+        for y in (0..self.height as i32).rev() {
+            for x in 0..self.width as i32 {
+                let pos = Position { x, y };
+                let portal = self
+                    .portals
+                    .iter()
+                    .position(|ends| ends.contains(&pos))
+                    .and_then(|pair| char::from_digit(pair as u32, 10));
+                let cell = if pos == self.spawn {
+                    'S'
+                } else if self.is_wall(&pos) {
+                    '#'
+                } else if let Some(digit) = portal {
+                    digit
+                } else if self.food_spots.contains(&pos) {
+                    'F'
+                } else {
+                    '.'
+                };
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+
+        if !self.hazards.is_empty() {
+            writeln!(f, "---")?;
+        }
+        for hazard in self.hazards.iter() {
+            let kind = match hazard.kind {
+                HazardKind::Saw => "saw",
+                HazardKind::Slider => "slider",
+                HazardKind::Pulse => "pulse",
+            };
+            write!(
+                f,
+                "{kind} {}",
+                self.write_cell(&hazard.origin, hazard.height)
+            )?;
+            if (hazard.width, hazard.height) != (1, 1) {
+                write!(f, " {}x{}", hazard.width, hazard.height)?;
+            }
+            match &hazard.motion {
+                HazardMotion::Still => {}
+                HazardMotion::Path(waypoints) => {
+                    write!(f, " path")?;
+                    for waypoint in waypoints {
+                        write!(f, " {}", self.write_cell(waypoint, hazard.height))?;
+                    }
+                }
+                HazardMotion::Bounce(direction) => {
+                    write!(f, " bounce {}", direction_name(*direction))?
+                }
+                HazardMotion::Pulse { on, off } => write!(f, " on {on} off {off}")?,
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum LevelError {
+    Io(std::io::Error),
+    NotUtf8,
+    /// Line number of the malformed header entry.
+    InvalidHeader(usize),
+    /// Line number and character of the unknown or duplicated cell.
+    InvalidCell(usize, char),
+    MissingSize,
+    MissingGrid,
+    MissingSpawn,
+    MissingFoodSpots,
+    MissingPar,
+    /// Line number of the malformed hazard.
+    InvalidHazard(usize),
+    /// Portal digit that does not appear exactly twice.
+    UnpairedPortal(char),
+    SizeMismatch,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read level: {err}"),
+            Self::NotUtf8 => write!(f, "level is not valid UTF-8"),
+            Self::InvalidHeader(line) => write!(f, "invalid header entry on line {line}"),
+            Self::InvalidCell(line, cell) => write!(f, "unexpected '{cell}' on line {line}"),
+            Self::MissingSize => write!(f, "header has no size"),
+            Self::MissingGrid => write!(f, "no '---' line before the grid"),
+            Self::MissingSpawn => write!(f, "grid has no spawn point"),
+            Self::MissingFoodSpots => write!(f, "food is set to spots but the grid has none"),
+            Self::MissingPar => write!(f, "header has a goal but no par"),
+            Self::InvalidHazard(line) => write!(f, "invalid hazard on line {line}"),
+            Self::UnpairedPortal(digit) => {
+                write!(f, "portal '{digit}' must appear exactly twice")
+            }
+            Self::SizeMismatch => write!(f, "grid does not match the size in the header"),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl From<std::io::Error> for LevelError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+#[derive(Default)]
+struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Level, LevelError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let source = std::str::from_utf8(&bytes).map_err(|_| LevelError::NotUtf8)?;
+            Level::parse(source)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level"]
+    }
+}
+
+/// The level being played this round.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub(crate) struct Arena(pub(crate) Level);
+
+#[derive(Component)]
+pub(crate) struct Wall;
+
+#[derive(Component)]
+pub(crate) struct Portal;
+
+/// A campaign or editor level that failed to load sends the player back to
+/// where it was picked rather than into an empty arena.
+fn build_arena(
+    mut commands: Commands,
+    mut arena: ResMut<Arena>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<LevelAssets>,
+    levels: Res<Assets<Level>>,
+    asset_server: Res<AssetServer>,
+    mode: Res<GameMode>,
+    seed: Res<GameSeed>,
+    campaign_run: Res<CampaignRun>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    arena.0 = match &current_level.0 {
+        Some(handle) => match levels.get(handle) {
+            Some(level) => level.clone(),
+            None => {
+                let path = asset_server
+                    .get_path(handle.id())
+                    .map_or_else(|| format!("{:?}", handle.id()), |path| path.to_string());
+                error!("Could not load level {path}, leaving the round");
+                next_state.set(if campaign_run.0.is_some() {
+                    GameState::LevelSelect
+                } else {
+                    GameState::Editor
+                });
+                return;
+            }
+        },
+        None if *mode == GameMode::Maze => maze::generate(seed.0),
+        None if *mode == GameMode::BattleRoyale => royale::arena(),
+        None if *mode == GameMode::Tron => tron::arena(),
+        None if *mode == GameMode::Hex => hex::arena(),
+        None => levels
+            .get(&level_assets.classic)
+            .cloned()
+            .unwrap_or_default(),
+    };
+
+    for wall in arena.walls.iter() {
+        spawn_wall(&mut commands, *wall);
+    }
+
+    for (pair, ends) in arena.portals.iter().enumerate() {
+        for end in ends {
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: PORTAL_COLORS[pair % PORTAL_COLORS.len()],
+                        ..default()
+                    },
+                    // under the snake passing through it
+                    transform: Transform::from_xyz(0.0, 0.0, -1.0),
+                    ..default()
+                },
+                Portal,
+                *end,
+                Size::square(0.9),
+                RoundCleanup,
+            ));
+        }
+    }
+}
+
+pub(crate) fn spawn_wall(commands: &mut Commands, position: Position) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle::default(),
+            Themed(Role::Wall),
+            Wall,
+            position,
+            Size::square(1.0),
+            RoundCleanup,
+        ))
+        .id()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIPPED: [&str; 9] = [
+        include_str!("../assets/levels/classic.level"),
+        include_str!("../assets/levels/campaign/01-warm-up.level"),
+        include_str!("../assets/levels/campaign/02-pillars.level"),
+        include_str!("../assets/levels/campaign/03-corridors.level"),
+        include_str!("../assets/levels/campaign/04-pantry.level"),
+        include_str!("../assets/levels/campaign/05-the-box.level"),
+        include_str!("../assets/levels/campaign/06-warp.level"),
+        include_str!("../assets/levels/campaign/07-sawmill.level"),
+        include_str!("../assets/levels/campaign/08-expanse.level"),
+    ];
+
+    /// Every cell kind, a goal and one hazard of each motion.
+    const EVERYTHING: &str = "\
+name: Everything
+size: 6x5
+facing: left
+speed: 250
+food: spots
+goal: length 8
+par: 30
+---
+######
+#0.F1#
+#.S..#
+#1..0#
+######
+---
+saw 1,1 path 1,1 4,1
+slider 1,2 2x1 bounce down
+pulse 3,2 on 3 off 5
+";
+
+    fn level(grid: &str) -> String {
+        format!("size: 4x3\n---\n{grid}")
+    }
+
+    fn round_trip(source: &str) {
+        let level = Level::parse(source).unwrap();
+        let written = level.to_string();
+        let reread = Level::parse(&written).unwrap();
+        assert_eq!(reread, level);
+        assert_eq!(reread.to_string(), written);
+    }
+
+    #[test]
+    fn round_trips_every_feature() {
+        round_trip(EVERYTHING);
+    }
+
+    #[test]
+    fn round_trips_shipped_levels() {
+        for source in SHIPPED {
+            round_trip(source);
+        }
+    }
+
+    #[test]
+    fn reads_cells_bottom_row_first() {
+        let level = Level::parse(EVERYTHING).unwrap();
+        assert_eq!(level.spawn, Position { x: 2, y: 2 });
+        assert_eq!(level.food_spots, vec![Position { x: 3, y: 3 }]);
+        assert_eq!(
+            level.portals,
+            vec![
+                [Position { x: 1, y: 3 }, Position { x: 4, y: 1 }],
+                [Position { x: 4, y: 3 }, Position { x: 1, y: 1 }],
+            ]
+        );
+        assert_eq!(level.hazards[0].origin, Position { x: 1, y: 3 });
+    }
+
+    #[test]
+    fn rejects_ragged_rows() {
+        let result = Level::parse(&level("....\n.S...\n....\n"));
+        assert!(matches!(result, Err(LevelError::SizeMismatch)));
+        let result = Level::parse(&level("....\n.S.\n....\n"));
+        assert!(matches!(result, Err(LevelError::SizeMismatch)));
+    }
+
+    #[test]
+    fn rejects_unknown_glyphs() {
+        let result = Level::parse(&level("....\n.S?.\n....\n"));
+        assert!(matches!(result, Err(LevelError::InvalidCell(4, '?'))));
+    }
+
+    #[test]
+    fn rejects_hazards_off_the_grid() {
+        for hazard in [
+            "saw 4,1",
+            "slider 3,1 2x1 bounce up",
+            "saw 1,1 path 1,1 1,3",
+            "saw 1,1 path 1,1 -1,1",
+        ] {
+            let result = Level::parse(&format!("{}---\n{hazard}\n", level("....\n.S..\n....\n")));
+            assert!(
+                matches!(result, Err(LevelError::InvalidHazard(7))),
+                "{hazard}"
+            );
+        }
+        let result = Level::parse(&format!(
+            "{}---\nsaw 0,0 path 0,0 3,2\n",
+            level("....\n.S..\n....\n")
+        ));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn rejects_missing_spawn() {
+        let result = Level::parse(&level("....\n....\n....\n"));
+        assert!(matches!(result, Err(LevelError::MissingSpawn)));
+    }
+
+    #[test]
+    fn rejects_duplicate_spawn() {
+        let result = Level::parse(&level("....\n.S..\n..S.\n"));
+        assert!(matches!(result, Err(LevelError::InvalidCell(5, 'S'))));
+    }
+}
@@ -1,13 +1,62 @@
+// Bevy systems routinely take many parameters and nested query filters
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use accessibility::AccessibilityPlugin;
+use audio::AudioPlugin;
 use bevy::prelude::*;
+use bevy_xpbd_2d::plugins::PhysicsPlugins;
+use bot::BotPlugin;
+use camera::CameraPlugin;
+use campaign::CampaignPlugin;
+use countdown::CountdownPlugin;
+use death::DeathPlugin;
+use editor::EditorPlugin;
+use enum_iterator::Sequence;
 use game::GamePlayingPlugin;
 use game_over::GameOverPlugin;
+use hazard::HazardPlugin;
+use hex::HexPlugin;
+use hud::HudPlugin;
+use level::{CurrentLevel, LevelPlugin};
 use loading::LoadingPlugin;
 use menu::MenuPlugin;
+use particles::ParticlePlugin;
+use ragdoll::RagdollPlugin;
+use royale::RoyalePlugin;
+use scoring::ScoringPlugin;
+use settings::SettingsPlugin;
+use skin::SkinPlugin;
+use slither::SlitherPlugin;
+use theme::ThemePlugin;
+use tron::TronPlugin;
 
+mod accessibility;
+mod audio;
+mod bot;
+mod camera;
+mod campaign;
+mod countdown;
+mod death;
+mod editor;
 mod game;
 mod game_over;
+mod hazard;
+mod hex;
+mod hud;
+mod level;
 mod loading;
+mod maze;
 mod menu;
+mod particles;
+mod ragdoll;
+mod royale;
+mod scoring;
+mod settings;
+mod skin;
+mod slither;
+mod storage;
+mod theme;
+mod tron;
 
 #[derive(States, Default, Clone, Eq, PartialEq, Debug, Hash)]
 enum GameState {
@@ -16,17 +65,134 @@ enum GameState {
     Game,
     Menu,
     GameOver,
+    /// Level editor, test plays go through `Game` and come back here.
+    Editor,
+    /// Campaign level list, levels are played in `Game`.
+    LevelSelect,
+    /// Shown when a campaign level's goal is reached.
+    LevelComplete,
+    /// Options saved between runs, reached from the menu.
+    Settings,
+}
+
+/// Phases of a round, only meaningful while in `GameState::Game`.
+#[derive(States, Default, Clone, Eq, PartialEq, Debug, Hash)]
+enum RoundState {
+    #[default]
+    Inactive,
+    Countdown,
+    Playing,
+    Dying,
+}
+
+/// Ruleset used for the next round, picked in the menu.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq, Sequence)]
+enum GameMode {
+    /// Grid movement, one cell per tick.
+    #[default]
+    Classic,
+    /// Free steering with physics-based collisions.
+    Slither,
+    /// Classic rules in a generated maze.
+    Maze,
+    /// The arena closes in from the border, alone for score or last snake
+    /// standing in versus.
+    BattleRoyale,
+    /// Light cycles: no food, every snake leaves a trail that never retracts.
+    Tron,
+    /// Six directions on a hexagonal board.
+    Hex,
+}
+
+impl GameMode {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Classic => "Classic",
+            Self::Slither => "Slither",
+            Self::Maze => "Maze",
+            Self::BattleRoyale => "Royale",
+            Self::Tron => "Tron",
+            Self::Hex => "Hex",
+        }
+    }
+}
+
+/// Run condition for the systems shared by every mode that moves cell by cell
+/// on the square grid. Hex mode falls back to it for square campaign and
+/// editor levels.
+fn grid_movement(mode: Res<GameMode>, current_level: Res<CurrentLevel>) -> bool {
+    match *mode {
+        GameMode::Slither => false,
+        GameMode::Hex => current_level.0.is_some(),
+        _ => true,
+    }
+}
+
+const MAX_PLAYERS: usize = 4;
+
+/// Local players sharing the keyboard and computer-controlled snakes joining
+/// them, picked in the menu. Slither and hex rounds and levels from the
+/// campaign or the editor are always played alone.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+struct Players {
+    humans: usize,
+    bots: usize,
+}
+
+impl Default for Players {
+    fn default() -> Self {
+        Self { humans: 1, bots: 0 }
+    }
+}
+
+/// Seeds everything generated for a round, so a round can be replayed.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+struct GameSeed(u64);
+
+impl Default for GameSeed {
+    fn default() -> Self {
+        Self(rand::random())
+    }
 }
 
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_state::<GameState>().add_plugins((
-            LoadingPlugin,
-            MenuPlugin,
-            GamePlayingPlugin,
-            GameOverPlugin,
-        ));
+        app.add_state::<GameState>()
+            .add_state::<RoundState>()
+            .insert_resource(GameMode::default())
+            .insert_resource(GameSeed::default())
+            .insert_resource(Players::default())
+            .add_plugins((
+                LoadingPlugin,
+                MenuPlugin,
+                LevelPlugin,
+                GamePlayingPlugin,
+                SlitherPlugin,
+                CountdownPlugin,
+                DeathPlugin,
+                RagdollPlugin,
+                HazardPlugin,
+                HudPlugin,
+                GameOverPlugin,
+                EditorPlugin,
+                CampaignPlugin,
+            ))
+            .add_plugins((
+                RoyalePlugin,
+                TronPlugin,
+                BotPlugin,
+                HexPlugin,
+                CameraPlugin,
+                SkinPlugin,
+                ThemePlugin,
+                SettingsPlugin,
+                AccessibilityPlugin,
+                ParticlePlugin,
+                ScoringPlugin,
+                AudioPlugin,
+            ))
+            .add_plugins(PhysicsPlugins::default());
     }
 }
@@ -1,7 +1,9 @@
 use bevy::prelude::*;
 use bevy_asset_loader::loading_state::{LoadingState, LoadingStateAppExt};
 
-use crate::GameState;
+use crate::{
+    audio::AudioAssets, level::LevelAssets, skin::SkinAssets, theme::ThemeAssets, GameState,
+};
 
 pub struct LoadingPlugin;
 
@@ -9,6 +11,10 @@ impl Plugin for LoadingPlugin {
     fn build(&self, app: &mut App) {
         app.add_loading_state(
             LoadingState::new(GameState::Loading).continue_to_state(GameState::Menu),
-        );
+        )
+        .add_collection_to_loading_state::<_, LevelAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, SkinAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, ThemeAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, AudioAssets>(GameState::Loading);
     }
 }
@@ -0,0 +1,305 @@
+//! Generated arenas for the Maze mode. The same seed always gives the same
+//! layout; every free cell can be reached from the spawn.
+
+use std::collections::{HashSet, VecDeque};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+    game::{Direction, Position},
+    level::Level,
+};
+
+/// Odd so that maze cells and the walls between them line up with the border.
+const MAZE_SIZE: i32 = 21;
+/// Cells cleared in every direction around the spawn.
+const SPAWN_CLEARANCE: i32 = 2;
+/// Chance for each wall left between two maze cells to be knocked down, a
+/// perfect maze has no loops and is hopeless for a growing snake.
+const BRAID_CHANCE: f64 = 0.3;
+const ROOM_ATTEMPTS: usize = 30;
+const MIN_ROOM_SIZE: i32 = 3;
+const MAX_ROOM_SIZE: i32 = 6;
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Right,
+    Direction::Down,
+    Direction::Left,
+];
+
+pub(crate) fn generate(seed: u64) -> Level {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // start solid and carve the free cells out
+    let mut walls = (0..MAZE_SIZE)
+        .flat_map(|x| (0..MAZE_SIZE).map(move |y| Position { x, y }))
+        .collect::<HashSet<Position>>();
+    let spawn = Position {
+        x: MAZE_SIZE / 2,
+        y: MAZE_SIZE / 2,
+    };
+
+    if rng.gen_bool(0.5) {
+        carve_maze(&mut walls, &mut rng);
+    } else {
+        carve_rooms(&mut walls, spawn, &mut rng);
+    }
+
+    for x in -SPAWN_CLEARANCE..=SPAWN_CLEARANCE {
+        for y in -SPAWN_CLEARANCE..=SPAWN_CLEARANCE {
+            walls.remove(&Position {
+                x: spawn.x + x,
+                y: spawn.y + y,
+            });
+        }
+    }
+    seal_unreachable(&mut walls, spawn);
+
+    // head down the longest free run
+    let facing = DIRECTIONS
+        .into_iter()
+        .max_by_key(|direction| free_run(&walls, spawn, *direction))
+        .unwrap_or(Direction::Up);
+
+    Level {
+        name: "Maze".to_string(),
+        width: MAZE_SIZE as u32,
+        height: MAZE_SIZE as u32,
+        spawn,
+        facing,
+        walls,
+        ..Level::default()
+    }
+}
+
+fn is_inner(pos: &Position) -> bool {
+    pos.x > 0 && pos.y > 0 && pos.x < MAZE_SIZE - 1 && pos.y < MAZE_SIZE - 1
+}
+
+/// Depth-first maze over the odd cells, then braided.
+fn carve_maze(walls: &mut HashSet<Position>, rng: &mut StdRng) {
+    let start = Position { x: 1, y: 1 };
+    walls.remove(&start);
+    let mut stack = vec![start];
+
+    while let Some(&cell) = stack.last() {
+        let neighbours = DIRECTIONS
+            .iter()
+            .map(|direction| {
+                let (dx, dy) = direction.offset();
+                Position {
+                    x: cell.x + dx * 2,
+                    y: cell.y + dy * 2,
+                }
+            })
+            .filter(|next| is_inner(next) && walls.contains(next))
+            .collect::<Vec<Position>>();
+
+        let Some(&next) = neighbours.choose(rng) else {
+            stack.pop();
+            continue;
+        };
+        walls.remove(&Position {
+            x: (cell.x + next.x) / 2,
+            y: (cell.y + next.y) / 2,
+        });
+        walls.remove(&next);
+        stack.push(next);
+    }
+
+    // walls between two cells have exactly one odd coordinate; go through them
+    // in a fixed order so the seed alone decides the result
+    for x in 1..MAZE_SIZE - 1 {
+        for y in 1..MAZE_SIZE - 1 {
+            if (x + y) % 2 == 1 && rng.gen_bool(BRAID_CHANCE) {
+                walls.remove(&Position { x, y });
+            }
+        }
+    }
+}
+
+/// Random rooms, each joined to the previous one, the first one to the spawn.
+fn carve_rooms(walls: &mut HashSet<Position>, spawn: Position, rng: &mut StdRng) {
+    let mut rooms: Vec<(Position, Position)> = Vec::new();
+
+    for _ in 0..ROOM_ATTEMPTS {
+        let width = rng.gen_range(MIN_ROOM_SIZE..=MAX_ROOM_SIZE);
+        let height = rng.gen_range(MIN_ROOM_SIZE..=MAX_ROOM_SIZE);
+        let min = Position {
+            x: rng.gen_range(1..MAZE_SIZE - width),
+            y: rng.gen_range(1..MAZE_SIZE - height),
+        };
+        let max = Position {
+            x: min.x + width - 1,
+            y: min.y + height - 1,
+        };
+
+        // keep a wall between rooms
+        let overlaps = rooms.iter().any(|(other_min, other_max)| {
+            min.x <= other_max.x + 1
+                && max.x + 1 >= other_min.x
+                && min.y <= other_max.y + 1
+                && max.y + 1 >= other_min.y
+        });
+        if overlaps {
+            continue;
+        }
+
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                walls.remove(&Position { x, y });
+            }
+        }
+
+        let center = room_center(&(min, max));
+        let previous = rooms.last().map(room_center).unwrap_or(spawn);
+        carve_corridor(walls, previous, center, rng);
+        rooms.push((min, max));
+    }
+}
+
+fn room_center((min, max): &(Position, Position)) -> Position {
+    Position {
+        x: (min.x + max.x) / 2,
+        y: (min.y + max.y) / 2,
+    }
+}
+
+/// L-shaped corridor, bending one way or the other.
+fn carve_corridor(walls: &mut HashSet<Position>, from: Position, to: Position, rng: &mut StdRng) {
+    let corner = if rng.gen_bool(0.5) {
+        Position { x: to.x, y: from.y }
+    } else {
+        Position { x: from.x, y: to.y }
+    };
+
+    for (start, end) in [(from, corner), (corner, to)] {
+        for x in start.x.min(end.x)..=start.x.max(end.x) {
+            for y in start.y.min(end.y)..=start.y.max(end.y) {
+                walls.remove(&Position { x, y });
+            }
+        }
+    }
+}
+
+/// Fills every free cell the spawn cannot reach.
+fn seal_unreachable(walls: &mut HashSet<Position>, spawn: Position) {
+    let mut reached = HashSet::from([spawn]);
+    let mut queue = VecDeque::from([spawn]);
+
+    while let Some(cell) = queue.pop_front() {
+        for direction in DIRECTIONS {
+            let (dx, dy) = direction.offset();
+            let next = Position {
+                x: cell.x + dx,
+                y: cell.y + dy,
+            };
+            let inside = next.x >= 0 && next.y >= 0 && next.x < MAZE_SIZE && next.y < MAZE_SIZE;
+            if inside && !walls.contains(&next) && reached.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    for x in 0..MAZE_SIZE {
+        for y in 0..MAZE_SIZE {
+            let pos = Position { x, y };
+            if !reached.contains(&pos) {
+                walls.insert(pos);
+            }
+        }
+    }
+}
+
+fn free_run(walls: &HashSet<Position>, from: Position, direction: Direction) -> i32 {
+    let (dx, dy) = direction.offset();
+    (1..MAZE_SIZE)
+        .take_while(|step| {
+            !walls.contains(&Position {
+                x: from.x + dx * step,
+                y: from.y + dy * step,
+            })
+        })
+        .count() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEEDS: u64 = 64;
+
+    /// Cells a freshly spawned snake covers: the head and its neck.
+    const STARTING_LENGTH: i32 = 2;
+
+    fn free_cells(level: &Level) -> HashSet<Position> {
+        (0..MAZE_SIZE)
+            .flat_map(|x| (0..MAZE_SIZE).map(move |y| Position { x, y }))
+            .filter(|pos| !level.walls.contains(pos))
+            .collect()
+    }
+
+    fn flood_fill(level: &Level, from: Position) -> HashSet<Position> {
+        let free = free_cells(level);
+        let mut reached = HashSet::from([from]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(cell) = queue.pop_front() {
+            for direction in DIRECTIONS {
+                let next = level.step(&cell, direction);
+                if free.contains(&next) && reached.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        reached
+    }
+
+    #[test]
+    fn same_seed_same_maze() {
+        for seed in 0..SEEDS {
+            assert_eq!(generate(seed), generate(seed));
+        }
+    }
+
+    #[test]
+    fn border_is_walled() {
+        for seed in 0..SEEDS {
+            let level = generate(seed);
+            assert!(free_cells(&level).iter().all(is_inner), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn every_free_cell_is_reachable_from_the_spawn() {
+        for seed in 0..SEEDS {
+            let level = generate(seed);
+            assert!(!level.walls.contains(&level.spawn), "seed {seed}");
+            assert_eq!(
+                flood_fill(&level, level.spawn),
+                free_cells(&level),
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn snake_fits_at_the_spawn() {
+        for seed in 0..SEEDS {
+            let level = generate(seed);
+            let (dx, dy) = level.facing.offset();
+            for step in 0..STARTING_LENGTH {
+                let segment = Position {
+                    x: level.spawn.x - dx * step,
+                    y: level.spawn.y - dy * step,
+                };
+                assert!(!level.walls.contains(&segment), "seed {seed}");
+            }
+            // and it does not run straight into a wall
+            assert!(
+                free_run(&level.walls, level.spawn, level.facing) >= SPAWN_CLEARANCE,
+                "seed {seed}"
+            );
+        }
+    }
+}
@@ -1,15 +1,34 @@
 use bevy::{app::AppExit, prelude::*};
+use enum_iterator::next_cycle;
 
-use crate::GameState;
+use crate::{
+    game::{BitePenalty, BiteRule, Direction},
+    skin::{piece_offset, Skin, SkinAssets, SkinProgress, HEAD, HORIZONTAL, TAIL},
+    theme::{Palette, Role, Themed},
+    GameMode, GameSeed, GameState, Players, MAX_PLAYERS,
+};
+
+/// Side of each piece in the skin preview.
+const PREVIEW_TILE: f32 = 24.0;
+/// Locked skins are previewed faded out.
+const LOCKED_ALPHA: f32 = 0.25;
 
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Menu), setup_menu);
+        app.add_systems(OnEnter(GameState::Menu), (setup_menu, reroll_seed));
         app.add_systems(
             Update,
-            handle_buttons_interaction.run_if(in_state(GameState::Menu)),
+            (
+                handle_buttons_interaction,
+                cycle_game_mode,
+                cycle_players,
+                cycle_bite_rule,
+                cycle_bite_penalty,
+                cycle_skin,
+            )
+                .run_if(in_state(GameState::Menu)),
         );
         app.add_systems(OnExit(GameState::Menu), cleanup_menu);
     }
@@ -21,15 +40,77 @@ struct Menu;
 #[derive(Component)]
 struct Play;
 
+#[derive(Component)]
+struct Settings;
+
 #[derive(Component)]
 struct Quit;
 
 #[derive(Component)]
-struct OpenLink(&'static str);
+struct Campaign;
+
+#[derive(Component)]
+struct Editor;
+
+#[derive(Component)]
+struct ModeButton;
+
+/// Cycles how many humans or bots take part.
+#[derive(Component, Clone, Copy)]
+enum PlayersButton {
+    Humans,
+    Bots,
+}
+
+impl PlayersButton {
+    fn label(&self, players: &Players) -> String {
+        match self {
+            Self::Humans => format!("Players: {}", players.humans),
+            Self::Bots => format!("Bots: {}", players.bots),
+        }
+    }
+}
+
+#[derive(Component)]
+struct BiteButton;
+
+#[derive(Component)]
+struct PenaltyButton;
+
+/// Browses the skins, holding the index of the one shown. Locked skins can be
+/// looked at but not worn.
+#[derive(Component)]
+struct SkinButton(usize);
+
+#[derive(Component)]
+struct SkinPreview;
 
-const GITHUB_URL: &str = "https://github.com/trankhacvy/oh-my-snake";
+fn setup_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mode: Res<GameMode>,
+    players: Res<Players>,
+    bite_rule: Res<BiteRule>,
+    bite_penalty: Res<BitePenalty>,
+    skin_assets: Res<SkinAssets>,
+    skins: Res<Assets<Skin>>,
+    skin_progress: Res<SkinProgress>,
+    palette: Res<Palette>,
+) {
+    let shown = skin_assets
+        .skins
+        .iter()
+        .position(|handle| {
+            skins
+                .get(handle)
+                .is_some_and(|skin| skin.name == skin_progress.selected)
+        })
+        .unwrap_or(0);
+    let shown_skin = skin_assets
+        .skins
+        .get(shown)
+        .and_then(|handle| skins.get(handle));
 
-fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands
         .spawn((
             NodeBundle {
@@ -39,17 +120,17 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                     flex_direction: FlexDirection::Column,
                     align_items: AlignItems::Center,
                     justify_content: JustifyContent::Center,
-                    row_gap: Val::Px(20.0),
+                    row_gap: Val::Px(14.0),
                     ..default()
                 },
-                background_color: BackgroundColor::from(Color::BLACK),
                 ..default()
             },
+            Themed(Role::Background),
             Menu,
         ))
         .with_children(|children| {
             // text
-            children.spawn(
+            children.spawn((
                 TextBundle::from_section(
                     // Accepts a `String` or any type that converts into a `String`, such as `&str`
                     "_Snake",
@@ -57,11 +138,208 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                         font: asset_server.load("fonts/KnightWarrior.otf"),
                         font_size: 60.0,
                         color: Color::WHITE,
-                        ..default()
                     },
                 ) // Set the alignment of the Text
                 .with_text_alignment(TextAlignment::Center),
-            );
+                Themed(Role::Text),
+            ));
+
+            // game mode button
+            children
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(220.0),
+                            height: Val::Px(40.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    Themed(Role::Button),
+                    ModeButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            format!("Mode: {}", mode.label()),
+                            TextStyle {
+                                font: asset_server.load("fonts/KnightWarrior.otf"),
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        Themed(Role::Text),
+                    ));
+                });
+
+            // players buttons, side by side
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|children| {
+                    for button in [PlayersButton::Humans, PlayersButton::Bots] {
+                        children
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(150.0),
+                                        height: Val::Px(40.0),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..Default::default()
+                                    },
+                                    ..Default::default()
+                                },
+                                Themed(Role::Button),
+                                button,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    TextBundle::from_section(
+                                        button.label(&players),
+                                        TextStyle {
+                                            font: asset_server.load("fonts/KnightWarrior.otf"),
+                                            font_size: 24.0,
+                                            color: Color::WHITE,
+                                        },
+                                    ),
+                                    Themed(Role::Text),
+                                ));
+                            });
+                    }
+                });
+
+            // bite rule button
+            children
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(220.0),
+                            height: Val::Px(40.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    Themed(Role::Button),
+                    BiteButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            format!("Bite: {}", bite_rule.label()),
+                            TextStyle {
+                                font: asset_server.load("fonts/KnightWarrior.otf"),
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        Themed(Role::Text),
+                    ));
+                });
+
+            // points lost per bitten segment
+            children
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(220.0),
+                            height: Val::Px(40.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    Themed(Role::Button),
+                    PenaltyButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            penalty_label(&bite_penalty),
+                            TextStyle {
+                                font: asset_server.load("fonts/KnightWarrior.otf"),
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        Themed(Role::Text),
+                    ));
+                });
+
+            // skin picker and preview, side by side
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|children| {
+                    children
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(220.0),
+                                    height: Val::Px(40.0),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            },
+                            Themed(Role::Button),
+                            SkinButton(shown),
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    skin_label(shown_skin, &skin_progress),
+                                    TextStyle {
+                                        font: asset_server.load("fonts/KnightWarrior.otf"),
+                                        font_size: 24.0,
+                                        color: Color::WHITE,
+                                    },
+                                ),
+                                Themed(Role::Text),
+                            ));
+                        });
+
+                    children
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Row,
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            SkinPreview,
+                        ))
+                        .with_children(|parent| {
+                            if let Some(skin) = shown_skin {
+                                spawn_skin_preview(
+                                    parent,
+                                    skin,
+                                    &palette,
+                                    skin_progress.is_unlocked(skin),
+                                );
+                            }
+                        });
+                });
 
             // play button
             children
@@ -74,24 +352,26 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                             align_items: AlignItems::Center,
                             ..Default::default()
                         },
-                        background_color: Color::VIOLET.into(),
                         ..Default::default()
                     },
+                    Themed(Role::ButtonPrimary),
                     Play,
                 ))
                 .with_children(|parent| {
-                    parent.spawn(TextBundle::from_section(
-                        "Play",
-                        TextStyle {
-                            font: asset_server.load("fonts/KnightWarrior.otf"),
-                            font_size: 24.0,
-                            color: Color::WHITE,
-                            ..default()
-                        },
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "Play",
+                            TextStyle {
+                                font: asset_server.load("fonts/KnightWarrior.otf"),
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        Themed(Role::Text),
                     ));
                 });
 
-            // quit button
+            // campaign button
             children
                 .spawn((
                     ButtonBundle {
@@ -102,22 +382,123 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                             align_items: AlignItems::Center,
                             ..Default::default()
                         },
-                        background_color: BackgroundColor::from(Color::ORANGE_RED),
                         ..Default::default()
                     },
-                    Quit,
+                    Themed(Role::ButtonSecondary),
+                    Campaign,
                 ))
                 .with_children(|parent| {
-                    parent.spawn(TextBundle::from_section(
-                        "Quit",
-                        TextStyle {
-                            font: asset_server.load("fonts/KnightWarrior.otf"),
-                            font_size: 24.0,
-                            color: Color::WHITE,
-                            ..default()
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "Campaign",
+                            TextStyle {
+                                font: asset_server.load("fonts/KnightWarrior.otf"),
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        Themed(Role::Text),
+                    ));
+                });
+
+            // editor button
+            children
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(160.0),
+                            height: Val::Px(40.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
                         },
+                        ..Default::default()
+                    },
+                    Themed(Role::ButtonTertiary),
+                    Editor,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "Editor",
+                            TextStyle {
+                                font: asset_server.load("fonts/KnightWarrior.otf"),
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        Themed(Role::Text),
                     ));
                 });
+
+            // settings and quit buttons, side by side
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|children| {
+                    children
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(150.0),
+                                    height: Val::Px(40.0),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            },
+                            Themed(Role::Button),
+                            Settings,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    "Settings",
+                                    TextStyle {
+                                        font: asset_server.load("fonts/KnightWarrior.otf"),
+                                        font_size: 24.0,
+                                        color: Color::WHITE,
+                                    },
+                                ),
+                                Themed(Role::Text),
+                            ));
+                        });
+                    children
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(150.0),
+                                    height: Val::Px(40.0),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            },
+                            Themed(Role::ButtonDanger),
+                            Quit,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    "Quit",
+                                    TextStyle {
+                                        font: asset_server.load("fonts/KnightWarrior.otf"),
+                                        font_size: 24.0,
+                                        color: Color::WHITE,
+                                    },
+                                ),
+                                Themed(Role::Text),
+                            ));
+                        });
+                });
         });
 
     commands
@@ -139,29 +520,28 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
         ))
         .with_children(|children| {
             children
-                .spawn((
-                    ButtonBundle {
-                        style: Style {
-                            width: Val::Px(140.0),
-                            height: Val::Px(50.0),
-                            justify_content: JustifyContent::SpaceAround,
-                            align_items: AlignItems::Center,
-                            ..default()
-                        },
-                        background_color: Color::NONE.into(),
-                        ..Default::default()
+                .spawn(ButtonBundle {
+                    style: Style {
+                        width: Val::Px(140.0),
+                        height: Val::Px(50.0),
+                        justify_content: JustifyContent::SpaceAround,
+                        align_items: AlignItems::Center,
+                        ..default()
                     },
-                    OpenLink(GITHUB_URL),
-                ))
+                    background_color: Color::NONE.into(),
+                    ..Default::default()
+                })
                 .with_children(|parent| {
-                    parent.spawn(TextBundle::from_section(
-                        "Source code",
-                        TextStyle {
-                            font_size: 20.0,
-                            color: Color::WHITE,
-                            font: asset_server.load("fonts/KnightWarrior.otf"),
-                            ..default()
-                        },
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "Source code",
+                            TextStyle {
+                                font_size: 20.0,
+                                color: Color::WHITE,
+                                font: asset_server.load("fonts/KnightWarrior.otf"),
+                            },
+                        ),
+                        Themed(Role::Text),
                     ));
                     parent.spawn(ImageBundle {
                         image: UiImage::new(asset_server.load("textures/github.png")),
@@ -175,42 +555,253 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
         });
 }
 
+/// Each visit to the menu starts from a new seed, "Play Again" keeps the same one.
+fn reroll_seed(mut seed: ResMut<GameSeed>) {
+    *seed = GameSeed::default();
+    info!("Game seed: {:x}", seed.0);
+}
+
 fn handle_buttons_interaction(
     mut next_state: ResMut<NextState<GameState>>,
     mut app_exit_events: EventWriter<AppExit>,
-    mut interaction_query: Query<
+    interaction_query: Query<
         (
             &Interaction,
-            &mut BackgroundColor,
             Option<&Play>,
+            Option<&Campaign>,
+            Option<&Editor>,
+            Option<&Settings>,
             Option<&Quit>,
         ),
         (Changed<Interaction>, With<Button>),
     >,
 ) {
-    for (interaction, mut color, maybe_btn_play, maybe_btn_quit) in &mut interaction_query {
-        match *interaction {
-            Interaction::Pressed => {
-                if let Some(_) = maybe_btn_play {
-                    next_state.set(GameState::Game);
-                } else if let Some(_) = maybe_btn_quit {
-                    app_exit_events.send(AppExit);
+    for (
+        interaction,
+        maybe_btn_play,
+        maybe_btn_campaign,
+        maybe_btn_editor,
+        maybe_btn_settings,
+        maybe_btn_quit,
+    ) in &interaction_query
+    {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if maybe_btn_play.is_some() {
+            next_state.set(GameState::Game);
+        } else if maybe_btn_campaign.is_some() {
+            next_state.set(GameState::LevelSelect);
+        } else if maybe_btn_editor.is_some() {
+            next_state.set(GameState::Editor);
+        } else if maybe_btn_settings.is_some() {
+            next_state.set(GameState::Settings);
+        } else if maybe_btn_quit.is_some() {
+            app_exit_events.send(AppExit);
+        }
+    }
+}
+
+fn cycle_game_mode(
+    mut mode: ResMut<GameMode>,
+    mut interaction_query: Query<
+        (&Interaction, &Children),
+        (Changed<Interaction>, With<ModeButton>),
+    >,
+    mut text_query: Query<&mut Text>,
+) {
+    for (interaction, children) in &mut interaction_query {
+        if *interaction == Interaction::Pressed {
+            *mode = next_cycle(&*mode).unwrap_or_default();
+            for child in children.iter() {
+                if let Ok(mut text) = text_query.get_mut(*child) {
+                    text.sections[0].value = format!("Mode: {}", mode.label());
+                }
+            }
+        }
+    }
+}
+
+/// At least one human, at most `MAX_PLAYERS` snakes in all; more humans leave
+/// less room for bots.
+fn cycle_players(
+    mut players: ResMut<Players>,
+    mut interaction_query: Query<(&Interaction, &PlayersButton), Changed<Interaction>>,
+    button_query: Query<(&PlayersButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (interaction, button) in &mut interaction_query {
+        if *interaction == Interaction::Pressed {
+            match button {
+                PlayersButton::Humans => {
+                    players.humans = players.humans % MAX_PLAYERS + 1;
+                    players.bots = players.bots.min(MAX_PLAYERS - players.humans);
+                }
+                PlayersButton::Bots => {
+                    players.bots = (players.bots + 1) % (MAX_PLAYERS - players.humans + 1);
+                }
+            }
+            for (button, children) in button_query.iter() {
+                for child in children.iter() {
+                    if let Ok(mut text) = text_query.get_mut(*child) {
+                        text.sections[0].value = button.label(&players);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn cycle_bite_rule(
+    mut bite_rule: ResMut<BiteRule>,
+    mut interaction_query: Query<
+        (&Interaction, &Children),
+        (Changed<Interaction>, With<BiteButton>),
+    >,
+    mut text_query: Query<&mut Text>,
+) {
+    for (interaction, children) in &mut interaction_query {
+        if *interaction == Interaction::Pressed {
+            *bite_rule = next_cycle(&*bite_rule).unwrap_or_default();
+            for child in children.iter() {
+                if let Ok(mut text) = text_query.get_mut(*child) {
+                    text.sections[0].value = format!("Bite: {}", bite_rule.label());
                 }
             }
-            Interaction::Hovered => {
-                if let Some(_) = maybe_btn_play {
-                    *color = Color::rgba(0.93, 0.51, 0.93, 0.5).into();
-                } else if let Some(_) = maybe_btn_quit {
-                    *color = Color::rgba(1.0, 0.27, 0.0, 0.5).into();
+        }
+    }
+}
+
+fn penalty_label(penalty: &BitePenalty) -> String {
+    format!("Bite penalty: {}", penalty.0)
+}
+
+fn cycle_bite_penalty(
+    mut bite_penalty: ResMut<BitePenalty>,
+    mut interaction_query: Query<
+        (&Interaction, &Children),
+        (Changed<Interaction>, With<PenaltyButton>),
+    >,
+    mut text_query: Query<&mut Text>,
+) {
+    for (interaction, children) in &mut interaction_query {
+        if *interaction == Interaction::Pressed {
+            *bite_penalty = bite_penalty.next();
+            bite_penalty.save();
+            for child in children.iter() {
+                if let Ok(mut text) = text_query.get_mut(*child) {
+                    text.sections[0].value = penalty_label(&bite_penalty);
                 }
             }
-            Interaction::None => {
-                if let Some(_) = maybe_btn_play {
-                    *color = Color::VIOLET.into();
-                } else if let Some(_) = maybe_btn_quit {
-                    *color = Color::ORANGE_RED.into();
+        }
+    }
+}
+
+fn skin_label(skin: Option<&Skin>, progress: &SkinProgress) -> String {
+    match skin {
+        Some(skin) if progress.is_unlocked(skin) => format!("Skin: {}", skin.name),
+        Some(skin) => format!("{}: {}", skin.name, skin.unlock.label()),
+        None => "Skin: None".to_string(),
+    }
+}
+
+/// A short snake heading right: tail, two body pieces and the head.
+fn spawn_skin_preview(parent: &mut ChildBuilder, skin: &Skin, palette: &Palette, unlocked: bool) {
+    let alpha = if unlocked { 1.0 } else { LOCKED_ALPHA };
+    let pieces = [
+        (
+            TAIL + piece_offset(Direction::Left),
+            skin.tail(palette),
+            0.6,
+        ),
+        (HORIZONTAL, skin.body(palette), 0.6),
+        (HORIZONTAL, skin.body(palette), 0.6),
+        (
+            HEAD + piece_offset(Direction::Right),
+            skin.head(palette),
+            0.8,
+        ),
+    ];
+
+    for (index, color, share) in pieces {
+        let color = *color.clone().set_a(color.a() * alpha);
+        let tile = Style {
+            width: Val::Px(PREVIEW_TILE),
+            height: Val::Px(PREVIEW_TILE),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        };
+
+        match &skin.atlas {
+            Some(atlas) => {
+                parent.spawn(AtlasImageBundle {
+                    style: tile,
+                    texture_atlas: atlas.clone(),
+                    texture_atlas_image: UiTextureAtlasImage { index, ..default() },
+                    // tints the atlas
+                    background_color: color.into(),
+                    ..default()
+                });
+            }
+            None => {
+                parent
+                    .spawn(NodeBundle {
+                        style: tile,
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Px(PREVIEW_TILE * share),
+                                height: Val::Px(PREVIEW_TILE * share),
+                                ..default()
+                            },
+                            background_color: color.into(),
+                            ..default()
+                        });
+                    });
+            }
+        }
+    }
+}
+
+fn cycle_skin(
+    mut commands: Commands,
+    skin_assets: Res<SkinAssets>,
+    skins: Res<Assets<Skin>>,
+    mut progress: ResMut<SkinProgress>,
+    palette: Res<Palette>,
+    mut interaction_query: Query<(&Interaction, &mut SkinButton, &Children), Changed<Interaction>>,
+    preview_query: Query<Entity, With<SkinPreview>>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (interaction, mut button, children) in &mut interaction_query {
+        if *interaction == Interaction::Pressed {
+            button.0 = (button.0 + 1) % skin_assets.skins.len().max(1);
+            let skin = skin_assets
+                .skins
+                .get(button.0)
+                .and_then(|handle| skins.get(handle));
+            if let Some(skin) = skin {
+                progress.select(skin);
+            }
+
+            for child in children.iter() {
+                if let Ok(mut text) = text_query.get_mut(*child) {
+                    text.sections[0].value = skin_label(skin, &progress);
                 }
             }
+            for preview in preview_query.iter() {
+                commands
+                    .entity(preview)
+                    .despawn_descendants()
+                    .with_children(|parent| {
+                        if let Some(skin) = skin {
+                            spawn_skin_preview(parent, skin, &palette, progress.is_unlocked(skin));
+                        }
+                    });
+            }
         }
     }
 }
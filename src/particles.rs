@@ -0,0 +1,158 @@
+//! Lightweight CPU particles: short-lived sprites that drift, slow down and
+//! fade. Bursts mark food being eaten and snakes dying. Reduced motion turns
+//! them all off.
+
+use std::ops::Range;
+
+use bevy::{prelude::*, transform::TransformSystem};
+use rand::random;
+
+use crate::{
+    accessibility::full_motion,
+    game::{player_colors, GameOverEvent, GrowthEvent, Player, RoundCleanup},
+    theme::{Palette, Role},
+    GameState,
+};
+
+/// Particles alive at once; emitters skip bursts past it.
+const MAX_PARTICLES: usize = 600;
+/// Share of its speed a particle keeps after a second.
+const DRAG: f32 = 0.05;
+/// Drawn over the arena and everything in it.
+const PARTICLE_Z: f32 = 5.0;
+
+const EAT_BURST: Burst = Burst {
+    count: 14,
+    speed: 40.0..140.0,
+    lifetime: 0.3..0.6,
+    size: 3.0..6.0,
+};
+const DEATH_SPARKS: Burst = Burst {
+    count: 40,
+    speed: 80.0..260.0,
+    lifetime: 0.4..0.9,
+    size: 2.0..5.0,
+};
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_particles).add_systems(
+            PostUpdate,
+            // heads are in place once transforms are propagated
+            (eat_burst, death_sparks)
+                .run_if(in_state(GameState::Game))
+                .run_if(full_motion)
+                .after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Ranges each particle of a burst is picked from.
+struct Burst {
+    count: usize,
+    /// In logical pixels per second.
+    speed: Range<f32>,
+    /// In seconds.
+    lifetime: Range<f32>,
+    /// Side in logical pixels.
+    size: Range<f32>,
+}
+
+#[derive(Component)]
+struct Particle {
+    velocity: Vec2,
+    lifetime: Timer,
+    color: Color,
+    size: f32,
+}
+
+fn pick(range: &Range<f32>) -> f32 {
+    range.start + random::<f32>() * (range.end - range.start)
+}
+
+/// Scatters a burst in every direction from `origin`.
+fn spawn_burst(commands: &mut Commands, origin: Vec2, burst: &Burst, color: Color) {
+    for _ in 0..burst.count {
+        let direction = Vec2::from_angle(random::<f32>() * std::f32::consts::TAU);
+        let size = pick(&burst.size);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(size)),
+                    ..default()
+                },
+                transform: Transform::from_translation(origin.extend(PARTICLE_Z)),
+                ..default()
+            },
+            Particle {
+                velocity: direction * pick(&burst.speed),
+                lifetime: Timer::from_seconds(pick(&burst.lifetime), TimerMode::Once),
+                color,
+                size,
+            },
+            RoundCleanup,
+        ));
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particle_query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let delta = time.delta_seconds();
+    let keep = DRAG.powf(delta);
+
+    for (entity, mut particle, mut transform, mut sprite) in particle_query.iter_mut() {
+        if particle.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let left = particle.lifetime.percent_left();
+        transform.translation += (particle.velocity * delta).extend(0.0);
+        particle.velocity *= keep;
+        sprite.color = *particle.color.clone().set_a(particle.color.a() * left);
+        sprite.custom_size = Some(Vec2::splat(particle.size * (0.5 + left / 2.0)));
+    }
+}
+
+/// The head is on the food's cell when the snake eats.
+fn eat_burst(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    mut growth_ev_reader: EventReader<GrowthEvent>,
+    head_query: Query<&GlobalTransform>,
+    particle_query: Query<(), With<Particle>>,
+) {
+    for GrowthEvent(snake) in growth_ev_reader.read() {
+        if particle_query.iter().len() >= MAX_PARTICLES {
+            continue;
+        }
+        if let Ok(transform) = head_query.get(*snake) {
+            let origin = transform.translation().truncate();
+            spawn_burst(&mut commands, origin, &EAT_BURST, palette.color(Role::Food));
+        }
+    }
+}
+
+fn death_sparks(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    mut game_over_ev_reader: EventReader<GameOverEvent>,
+    head_query: Query<(&GlobalTransform, Option<&Player>)>,
+    particle_query: Query<(), With<Particle>>,
+) {
+    for GameOverEvent(snake) in game_over_ev_reader.read() {
+        if particle_query.iter().len() >= MAX_PARTICLES {
+            continue;
+        }
+        if let Ok((transform, player)) = head_query.get(*snake) {
+            let (color, _) = player_colors(player.map_or(0, |player| player.0), &palette);
+            let origin = transform.translation().truncate();
+            spawn_burst(&mut commands, origin, &DEATH_SPARKS, color);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_xpbd_2d::prelude::{
+    AngularVelocity, Collider, DistanceJoint, Gravity, Joint, LinearVelocity, Restitution,
+    RigidBody, Sensor,
+};
+use rand::random;
+
+use crate::{
+    death::DeathStyle,
+    game::{arena_bounds, Dead, Position, RoundCleanup, SnakeBody},
+    hex::HexPosition,
+    level::Arena,
+    slither::FreePosition,
+    GameState, RoundState,
+};
+
+const RAGDOLL_GRAVITY: f32 = 900.0;
+const RAGDOLL_BURST_SPEED: f32 = 350.0;
+const WALL_THICKNESS: f32 = 40.0;
+const FADE_DELAY: Duration = Duration::from_millis(1200);
+const FADE_TIME: Duration = Duration::from_millis(800);
+
+pub struct RagdollPlugin;
+
+impl Plugin for RagdollPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Gravity(Vec2::NEG_Y * RAGDOLL_GRAVITY))
+            .insert_resource(RagdollFade::default());
+
+        app.add_systems(
+            OnEnter(RoundState::Dying),
+            start_ragdoll.run_if(resource_equals(DeathStyle::Ragdoll)),
+        )
+        .add_systems(
+            Update,
+            fade_out
+                .run_if(resource_equals(DeathStyle::Ragdoll))
+                .run_if(in_state(RoundState::Dying)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct FadeOverlay;
+
+#[derive(Resource)]
+struct RagdollFade {
+    delay: Timer,
+    fade: Timer,
+}
+
+impl Default for RagdollFade {
+    fn default() -> Self {
+        Self {
+            delay: Timer::new(FADE_DELAY, TimerMode::Once),
+            fade: Timer::new(FADE_TIME, TimerMode::Once),
+        }
+    }
+}
+
+fn start_ragdoll(
+    mut commands: Commands,
+    mut fade: ResMut<RagdollFade>,
+    arena: Res<Arena>,
+    body_query: Query<&SnakeBody, With<Dead>>,
+    primary_query: Query<&Window, With<PrimaryWindow>>,
+    transform_query: Query<&Transform>,
+) {
+    *fade = RagdollFade::default();
+
+    let Ok(window) = primary_query.get_single() else {
+        return;
+    };
+    let bounds = arena_bounds(window, &arena);
+
+    // arena walls, just outside the playable area
+    let walls = [
+        (
+            Vec2::new(bounds.center().x, bounds.min.y - WALL_THICKNESS / 2.0),
+            Vec2::new(bounds.width() + WALL_THICKNESS * 2.0, WALL_THICKNESS),
+        ),
+        (
+            Vec2::new(bounds.center().x, bounds.max.y + WALL_THICKNESS / 2.0),
+            Vec2::new(bounds.width() + WALL_THICKNESS * 2.0, WALL_THICKNESS),
+        ),
+        (
+            Vec2::new(bounds.min.x - WALL_THICKNESS / 2.0, bounds.center().y),
+            Vec2::new(WALL_THICKNESS, bounds.height()),
+        ),
+        (
+            Vec2::new(bounds.max.x + WALL_THICKNESS / 2.0, bounds.center().y),
+            Vec2::new(WALL_THICKNESS, bounds.height()),
+        ),
+    ];
+    for (center, size) in walls {
+        commands.spawn((
+            TransformBundle::from_transform(Transform::from_translation(center.extend(0.0))),
+            RigidBody::Static,
+            Collider::cuboid(size.x, size.y),
+            RoundCleanup,
+        ));
+    }
+
+    // the head may have died outside the arena, keep every segment inside the walls
+    for segment in body_query.iter().flat_map(|body| body.iter()) {
+        let Ok(transform) = transform_query.get(*segment) else {
+            continue;
+        };
+        let half_size = transform.scale.truncate() / 2.0;
+        let translation = transform
+            .translation
+            .truncate()
+            .clamp(bounds.min + half_size, bounds.max - half_size);
+
+        commands
+            .entity(*segment)
+            .remove::<(Position, FreePosition, HexPosition, Sensor)>()
+            .insert((
+                Transform {
+                    translation: translation.extend(transform.translation.z),
+                    ..*transform
+                },
+                RigidBody::Dynamic,
+                // sprites are unit quads scaled to the tile size, the collider follows that scale
+                Collider::cuboid(1.0, 1.0),
+                Restitution::new(0.4),
+                LinearVelocity(
+                    Vec2::new(random::<f32>() * 2.0 - 1.0, random::<f32>()) * RAGDOLL_BURST_SPEED,
+                ),
+                AngularVelocity((random::<f32>() * 2.0 - 1.0) * 10.0),
+            ));
+    }
+
+    for pair in body_query.iter().flat_map(|body| body.windows(2)) {
+        let (Ok(first), Ok(second)) = (transform_query.get(pair[0]), transform_query.get(pair[1]))
+        else {
+            continue;
+        };
+        let rest_length = first.translation.distance(second.translation);
+        // segments this far apart sit on either side of a portal, tying them
+        // together would drag one across the arena
+        if rest_length > first.scale.max_element().max(second.scale.max_element()) * 2.5 {
+            continue;
+        }
+
+        commands.spawn((
+            DistanceJoint::new(pair[0], pair[1])
+                .with_rest_length(rest_length)
+                .with_compliance(0.0001),
+            RoundCleanup,
+        ));
+    }
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.0).into(),
+            ..default()
+        },
+        FadeOverlay,
+        RoundCleanup,
+    ));
+}
+
+fn fade_out(
+    time: Res<Time>,
+    mut fade: ResMut<RagdollFade>,
+    mut overlay_query: Query<&mut BackgroundColor, With<FadeOverlay>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !fade.delay.tick(time.delta()).finished() {
+        return;
+    }
+
+    fade.fade.tick(time.delta());
+    for mut color in overlay_query.iter_mut() {
+        color.0.set_a(fade.fade.percent());
+    }
+
+    if fade.fade.just_finished() {
+        next_state.set(GameState::GameOver);
+    }
+}
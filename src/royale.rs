@@ -0,0 +1,197 @@
+//! Battle royale: every little while the outer ring of the arena turns into
+//! wall, after flashing a warning over the cells about to close.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    accessibility::full_motion,
+    game::{Direction, Food, GameOverEvent, Position, RoundCleanup, RoundSetup, Size, SnakeBody},
+    level::{spawn_wall, Arena, CurrentLevel, Level},
+    theme::{Role, Themed},
+    GameMode, GameState, RoundState,
+};
+
+const ARENA_SIZE: i32 = 20;
+const SHRINK_INTERVAL: Duration = Duration::from_secs(10);
+/// How long the closing ring flashes before it turns into wall.
+const WARNING_TIME: Duration = Duration::from_secs(3);
+const WARNING_FLASH: Duration = Duration::from_millis(200);
+/// The arena stops closing in once the open area is this wide.
+const MIN_OPEN_SIZE: i32 = 6;
+
+pub struct RoyalePlugin;
+
+impl Plugin for RoyalePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Shrink::default());
+
+        app.add_systems(
+            OnEnter(GameState::Game),
+            reset_shrink.in_set(RoundSetup::Reset),
+        )
+        .add_systems(
+            Update,
+            (shrink_arena, flash_warning.run_if(full_motion))
+                .chain()
+                .run_if(in_state(RoundState::Playing))
+                .run_if(royale_round),
+        );
+    }
+}
+
+/// The empty arena battle royale rounds start in.
+pub(crate) fn arena() -> Level {
+    Level {
+        name: "Battle Royale".to_string(),
+        width: ARENA_SIZE as u32,
+        height: ARENA_SIZE as u32,
+        spawn: Position {
+            x: ARENA_SIZE / 2,
+            y: ARENA_SIZE / 2,
+        },
+        facing: Direction::Up,
+        ..Level::default()
+    }
+}
+
+/// Levels picked from the campaign or the editor never shrink.
+fn royale_round(mode: Res<GameMode>, current_level: Res<CurrentLevel>) -> bool {
+    *mode == GameMode::BattleRoyale && current_level.0.is_none()
+}
+
+#[derive(Resource)]
+struct Shrink {
+    timer: Timer,
+    flash: Timer,
+    /// Distance from the border of the next ring to close.
+    ring: i32,
+}
+
+impl Default for Shrink {
+    fn default() -> Self {
+        Self {
+            timer: Timer::new(SHRINK_INTERVAL, TimerMode::Repeating),
+            flash: Timer::new(WARNING_FLASH, TimerMode::Repeating),
+            ring: 0,
+        }
+    }
+}
+
+impl Shrink {
+    fn has_room(&self, arena: &Level) -> bool {
+        arena.width.min(arena.height) as i32 - 2 * (self.ring + 1) >= MIN_OPEN_SIZE
+    }
+}
+
+/// Cell flashing over the ring about to close.
+#[derive(Component)]
+struct ShrinkWarning;
+
+fn reset_shrink(mut shrink: ResMut<Shrink>) {
+    *shrink = Shrink::default();
+}
+
+/// Free cells at distance `ring` from the border.
+fn ring_cells(arena: &Level, ring: i32) -> Vec<Position> {
+    let (width, height) = (arena.width as i32, arena.height as i32);
+    (0..width)
+        .flat_map(|x| (0..height).map(move |y| Position { x, y }))
+        .filter(|pos| {
+            pos.x
+                .min(pos.y)
+                .min(width - 1 - pos.x)
+                .min(height - 1 - pos.y)
+                == ring
+        })
+        .filter(|pos| !arena.is_wall(pos))
+        .collect()
+}
+
+fn shrink_arena(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut arena: ResMut<Arena>,
+    mut shrink: ResMut<Shrink>,
+    warning_query: Query<Entity, With<ShrinkWarning>>,
+    food_query: Query<(Entity, &Position), With<Food>>,
+    snake_query: Query<(Entity, &SnakeBody)>,
+    positions_query: Query<&Position>,
+    mut game_over_ev_writer: EventWriter<GameOverEvent>,
+) {
+    if !shrink.has_room(&arena) {
+        return;
+    }
+
+    shrink.timer.tick(time.delta());
+    let cells = ring_cells(&arena, shrink.ring);
+
+    if warning_query.is_empty() && shrink.timer.remaining() <= WARNING_TIME {
+        for cell in cells.iter() {
+            commands.spawn((
+                SpriteBundle {
+                    // under the snake crossing it
+                    transform: Transform::from_xyz(0.0, 0.0, -0.5),
+                    ..default()
+                },
+                Themed(Role::Warning),
+                ShrinkWarning,
+                *cell,
+                Size::square(1.0),
+                RoundCleanup,
+            ));
+        }
+    }
+
+    if !shrink.timer.just_finished() {
+        return;
+    }
+
+    for warning in warning_query.iter() {
+        commands.entity(warning).despawn();
+    }
+
+    for cell in cells.iter() {
+        arena.walls.insert(*cell);
+        spawn_wall(&mut commands, *cell);
+    }
+
+    // food is buried, spawning picks a new spot for it
+    for (entity, pos) in food_query.iter() {
+        if cells.contains(pos) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    // any part of a snake still on the ring is crushed
+    for (snake, body) in snake_query.iter() {
+        let caught = body.iter().any(|segment| {
+            positions_query
+                .get(*segment)
+                .is_ok_and(|pos| cells.contains(pos))
+        });
+        if caught {
+            game_over_ev_writer.send(GameOverEvent(snake));
+        }
+    }
+
+    shrink.ring += 1;
+}
+
+fn flash_warning(
+    time: Res<Time>,
+    mut shrink: ResMut<Shrink>,
+    mut warning_query: Query<&mut Visibility, With<ShrinkWarning>>,
+) {
+    if warning_query.is_empty() || !shrink.flash.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    for mut visibility in warning_query.iter_mut() {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+}
@@ -0,0 +1,258 @@
+//! Scoring for the first player. Food eaten in quick succession builds a
+//! streak that raises a multiplier; once the snake goes too long without
+//! eating, the multiplier falls back a step at a time. Every pickup floats a
+//! "+N" over the food.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::{
+    accessibility::Accessibility,
+    campaign::CampaignRun,
+    game::{
+        BitePenalty, CutEvent, EndlessBest, GrowthEvent, HighScore, Player, RoundCleanup,
+        RoundSetup, SpeedLevel,
+    },
+    theme::{Palette, Role},
+    GameMode, GameState, RoundState,
+};
+
+/// Time allowed between two pickups to keep the streak going, and between
+/// each step the multiplier falls back once it is broken.
+const COMBO_WINDOW: Duration = Duration::from_millis(2500);
+/// Pickups in a row needed for each step of the multiplier.
+const STREAK_PER_STEP: usize = 3;
+const MAX_MULTIPLIER: usize = 5;
+
+const POPUP_TIME: Duration = Duration::from_millis(800);
+/// How fast popups rise, in logical pixels per second.
+const POPUP_SPEED: f32 = 40.0;
+const POPUP_FONT_SIZE: f32 = 20.0;
+/// Drawn over the arena and the particles.
+const POPUP_Z: f32 = 6.0;
+
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ScoreBoard::default())
+            .insert_resource(ComboTimer::default())
+            .add_event::<PointsEvent>();
+
+        app.add_systems(
+            OnEnter(GameState::Game),
+            reset_score.in_set(RoundSetup::Reset),
+        )
+        .add_systems(
+            Update,
+            (decay_combo, update_score)
+                .chain()
+                .run_if(in_state(RoundState::Playing)),
+        )
+        .add_systems(Update, float_popups)
+        .add_systems(
+            PostUpdate,
+            // the head is on the food's cell once transforms are propagated
+            spawn_popups
+                .run_if(in_state(GameState::Game))
+                .after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Score of the first player.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ScoreBoard {
+    /// Points earned before any multiplier.
+    pub(crate) base: usize,
+    /// Points added on top by the multiplier.
+    pub(crate) bonus: usize,
+    pub(crate) multiplier: usize,
+    /// Pickups in a row, each within the combo window of the last.
+    pub(crate) streak: usize,
+}
+
+impl Default for ScoreBoard {
+    fn default() -> Self {
+        Self {
+            base: 0,
+            bonus: 0,
+            multiplier: 1,
+            streak: 0,
+        }
+    }
+}
+
+impl ScoreBoard {
+    pub(crate) fn total(&self) -> usize {
+        self.base + self.bonus
+    }
+
+    /// Adds `points` times the multiplier, returning what was added.
+    pub(crate) fn award(&mut self, points: usize) -> usize {
+        let earned = points * self.multiplier;
+        self.base += points;
+        self.bonus += earned - points;
+        earned
+    }
+
+    /// Takes points away, from the bonus first, and breaks the streak.
+    fn penalize(&mut self, points: usize) {
+        let from_bonus = points.min(self.bonus);
+        self.bonus -= from_bonus;
+        self.base = self.base.saturating_sub(points - from_bonus);
+        self.streak = 0;
+        self.multiplier = 1;
+    }
+
+    fn extend_streak(&mut self) {
+        self.streak += 1;
+        self.multiplier = (1 + self.streak / STREAK_PER_STEP).min(MAX_MULTIPLIER);
+    }
+
+    /// One step down the multiplier, with the streak to match.
+    fn decay(&mut self) {
+        self.multiplier = self.multiplier.saturating_sub(1).max(1);
+        self.streak = (self.multiplier - 1) * STREAK_PER_STEP;
+    }
+}
+
+/// Runs down between pickups; kept apart from `ScoreBoard` so that the HUD
+/// only updates when the score does.
+#[derive(Resource)]
+struct ComboTimer(Timer);
+
+impl Default for ComboTimer {
+    fn default() -> Self {
+        Self(Timer::new(COMBO_WINDOW, TimerMode::Once))
+    }
+}
+
+/// Points the snake with this head just earned.
+#[derive(Event)]
+struct PointsEvent {
+    snake: Entity,
+    points: usize,
+}
+
+/// Rises and fades over the cell where points were earned.
+#[derive(Component)]
+struct ScorePopup(Timer);
+
+fn reset_score(mut scoreboard: ResMut<ScoreBoard>, mut combo: ResMut<ComboTimer>) {
+    *scoreboard = ScoreBoard::default();
+    *combo = ComboTimer::default();
+}
+
+fn decay_combo(time: Res<Time>, mut combo: ResMut<ComboTimer>, mut scoreboard: ResMut<ScoreBoard>) {
+    if scoreboard.multiplier == 1 && scoreboard.streak == 0 {
+        return;
+    }
+    if combo.0.tick(time.delta()).just_finished() {
+        scoreboard.decay();
+        combo.0.reset();
+    }
+}
+
+fn update_score(
+    bite_penalty: Res<BitePenalty>,
+    mut scoreboard: ResMut<ScoreBoard>,
+    mut combo: ResMut<ComboTimer>,
+    mode: Res<GameMode>,
+    run: Res<CampaignRun>,
+    mut high_score: ResMut<HighScore>,
+    mut endless_best: ResMut<EndlessBest>,
+    mut speed_level: ResMut<SpeedLevel>,
+    mut growth_ev_reader: EventReader<GrowthEvent>,
+    mut cut_ev_reader: EventReader<CutEvent>,
+    mut points_ev_writer: EventWriter<PointsEvent>,
+    player_query: Query<&Player>,
+) {
+    for CutEvent { snake, segments } in cut_ev_reader.read() {
+        if player_query.get(*snake).is_ok_and(|player| player.0 == 0) {
+            scoreboard.penalize(bite_penalty.0 * segments);
+        }
+    }
+
+    for GrowthEvent(snake) in growth_ev_reader.read() {
+        if player_query
+            .get(*snake)
+            .map_or(true, |player| player.0 != 0)
+        {
+            continue;
+        }
+
+        scoreboard.extend_streak();
+        combo.0.reset();
+        let points = scoreboard.award(1);
+        points_ev_writer.send(PointsEvent {
+            snake: *snake,
+            points,
+        });
+
+        if scoreboard.total() > high_score.0 {
+            high_score.0 = scoreboard.total();
+        }
+        if *mode == GameMode::Classic && run.0.is_none() && scoreboard.total() > endless_best.0 {
+            endless_best.0 = scoreboard.total();
+        }
+        let level = SpeedLevel::for_score(scoreboard.total());
+        if level.0 != speed_level.0 {
+            *speed_level = level;
+        }
+    }
+}
+
+fn spawn_popups(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    palette: Res<Palette>,
+    mut points_ev_reader: EventReader<PointsEvent>,
+    head_query: Query<&GlobalTransform>,
+) {
+    for PointsEvent { snake, points } in points_ev_reader.read() {
+        let Ok(transform) = head_query.get(*snake) else {
+            continue;
+        };
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("+{points}"),
+                    TextStyle {
+                        font: asset_server.load("fonts/KnightWarrior.otf"),
+                        font_size: POPUP_FONT_SIZE,
+                        color: palette.color(Role::TextAccent),
+                    },
+                ),
+                transform: Transform::from_translation(
+                    transform.translation().truncate().extend(POPUP_Z),
+                ),
+                ..default()
+            },
+            ScorePopup(Timer::new(POPUP_TIME, TimerMode::Once)),
+            RoundCleanup,
+        ));
+    }
+}
+
+/// Reduced motion leaves popups in place to fade.
+fn float_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    accessibility: Res<Accessibility>,
+    mut popup_query: Query<(Entity, &mut ScorePopup, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut popup, mut transform, mut text) in popup_query.iter_mut() {
+        if popup.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if !accessibility.reduced_motion {
+            transform.translation.y += POPUP_SPEED * time.delta_seconds();
+        }
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(popup.0.percent_left());
+        }
+    }
+}
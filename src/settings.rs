@@ -0,0 +1,404 @@
+//! Settings screen, reached from the menu. Each option is a button that cycles
+//! through its values, volumes are sliders; the resources behind them save
+//! themselves.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use enum_iterator::next_cycle;
+
+use crate::{
+    accessibility::Accessibility,
+    audio::{AudioSettings, Channel},
+    death::DeathStyle,
+    theme::{selected_theme, Role, Theme, ThemeAssets, ThemeSettings, Themed},
+    GameState,
+};
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Settings), setup_settings)
+            .add_systems(
+                Update,
+                (
+                    cycle_settings,
+                    drag_sliders,
+                    update_labels,
+                    update_sliders,
+                    back_to_menu,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Settings)),
+            )
+            .add_systems(OnExit(GameState::Settings), cleanup_settings);
+    }
+}
+
+#[derive(Component)]
+struct SettingsUI;
+
+/// Cycles one option.
+#[derive(Component, Clone, Copy)]
+enum SettingButton {
+    Theme,
+    Patterns,
+    TextScale,
+    Motion,
+    Death,
+    Sound,
+}
+
+impl SettingButton {
+    const ALL: [Self; 6] = [
+        Self::Theme,
+        Self::Patterns,
+        Self::TextScale,
+        Self::Motion,
+        Self::Death,
+        Self::Sound,
+    ];
+
+    fn label(
+        &self,
+        theme_assets: &ThemeAssets,
+        themes: &Assets<Theme>,
+        theme_settings: &ThemeSettings,
+        accessibility: &Accessibility,
+        death_style: &DeathStyle,
+        audio: &AudioSettings,
+    ) -> String {
+        let on_off = |value: bool| if value { "On" } else { "Off" };
+        match self {
+            Self::Theme => theme_label(theme_assets, themes, theme_settings),
+            Self::Patterns => format!("Shapes: {}", on_off(accessibility.patterns)),
+            Self::TextScale => format!("Text: {}", accessibility.text_scale.label()),
+            Self::Motion => format!("Reduce motion: {}", on_off(accessibility.reduced_motion)),
+            Self::Death => format!("Death: {}", death_style.label()),
+            Self::Sound => format!("Sound: {}", on_off(!audio.muted)),
+        }
+    }
+}
+
+/// Track of a volume slider, set by pressing or dragging along it.
+#[derive(Component)]
+struct VolumeSlider(Channel);
+
+/// Part of a slider's track filled up to its volume.
+#[derive(Component)]
+struct SliderFill(Channel);
+
+#[derive(Component)]
+struct VolumeLabel(Channel);
+
+#[derive(Component)]
+struct BackButton;
+
+/// Volumes snap to steps this size.
+const VOLUME_STEP: f32 = 0.05;
+
+fn volume_label(audio: &AudioSettings, channel: Channel) -> String {
+    let percent = (audio.volume(channel) * 100.0).round();
+    format!("{} {percent}%", channel.label())
+}
+
+fn theme_label(
+    theme_assets: &ThemeAssets,
+    themes: &Assets<Theme>,
+    settings: &ThemeSettings,
+) -> String {
+    let name = selected_theme(theme_assets, themes, settings)
+        .and_then(|handle| themes.get(handle))
+        .map_or("?", |theme| theme.name.as_str());
+    format!("Theme: {name}")
+}
+
+fn setup_settings(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme_assets: Res<ThemeAssets>,
+    themes: Res<Assets<Theme>>,
+    theme_settings: Res<ThemeSettings>,
+    accessibility: Res<Accessibility>,
+    death_style: Res<DeathStyle>,
+    audio: Res<AudioSettings>,
+) {
+    let font = asset_server.load("fonts/KnightWarrior.otf");
+    let text = |value: String, font_size: f32| {
+        (
+            TextBundle::from_section(
+                value,
+                TextStyle {
+                    font: font.clone(),
+                    font_size,
+                    ..default()
+                },
+            ),
+            Themed(Role::Text),
+        )
+    };
+    let button = |role: Role, width: f32| {
+        (
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(width),
+                    height: Val::Px(40.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            Themed(role),
+        )
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            },
+            Themed(Role::Background),
+            SettingsUI,
+        ))
+        .with_children(|children| {
+            children.spawn(text("Settings".to_string(), 48.0));
+
+            for setting in SettingButton::ALL {
+                let label = setting.label(
+                    &theme_assets,
+                    &themes,
+                    &theme_settings,
+                    &accessibility,
+                    &death_style,
+                    &audio,
+                );
+                children
+                    .spawn((button(Role::Button, 300.0), setting))
+                    .with_children(|parent| {
+                        parent.spawn(text(label, 24.0));
+                    });
+            }
+
+            for channel in Channel::ALL {
+                children
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(300.0),
+                            justify_content: JustifyContent::SpaceBetween,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            text(volume_label(&audio, channel), 20.0),
+                            VolumeLabel(channel),
+                        ));
+                        row.spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(150.0),
+                                    height: Val::Px(20.0),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            Themed(Role::Button),
+                            VolumeSlider(channel),
+                        ))
+                        .with_children(|track| {
+                            track.spawn((
+                                NodeBundle {
+                                    style: Style {
+                                        width: Val::Percent(audio.volume(channel) * 100.0),
+                                        height: Val::Percent(100.0),
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                                Themed(Role::ButtonPrimary),
+                                SliderFill(channel),
+                            ));
+                        });
+                    });
+            }
+
+            children
+                .spawn((button(Role::ButtonDanger, 160.0), BackButton))
+                .with_children(|parent| {
+                    parent.spawn(text("Back".to_string(), 24.0));
+                });
+        });
+}
+
+fn cycle_settings(
+    theme_assets: Res<ThemeAssets>,
+    themes: Res<Assets<Theme>>,
+    mut theme_settings: ResMut<ThemeSettings>,
+    mut accessibility: ResMut<Accessibility>,
+    mut death_style: ResMut<DeathStyle>,
+    mut audio: ResMut<AudioSettings>,
+    interaction_query: Query<(&Interaction, &SettingButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            SettingButton::Theme => {
+                let current = selected_theme(&theme_assets, &themes, &theme_settings);
+                let index = theme_assets
+                    .themes
+                    .iter()
+                    .position(|handle| Some(handle) == current)
+                    .map_or(0, |index| index + 1);
+                let next = theme_assets.themes[index % theme_assets.themes.len()].clone();
+                if let Some(theme) = themes.get(&next) {
+                    theme_settings.select(theme);
+                }
+            }
+            SettingButton::Patterns => {
+                accessibility.patterns = !accessibility.patterns;
+                accessibility.save();
+            }
+            SettingButton::TextScale => {
+                accessibility.text_scale =
+                    next_cycle(&accessibility.text_scale).unwrap_or_default();
+                accessibility.save();
+            }
+            SettingButton::Motion => {
+                accessibility.reduced_motion = !accessibility.reduced_motion;
+                accessibility.save();
+            }
+            SettingButton::Death => {
+                *death_style = next_cycle(&*death_style).unwrap_or_default();
+                death_style.save();
+            }
+            SettingButton::Sound => {
+                audio.muted = !audio.muted;
+                audio.save();
+            }
+        }
+    }
+}
+
+/// Sets a volume from where the cursor is along a pressed slider, saving once
+/// it is let go.
+fn drag_sliders(
+    mut dragging: Local<bool>,
+    mut audio: ResMut<AudioSettings>,
+    primary_query: Query<&Window, With<PrimaryWindow>>,
+    slider_query: Query<(&Interaction, &VolumeSlider, &Node, &GlobalTransform)>,
+) {
+    let cursor = primary_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    let mut pressed = false;
+
+    for (interaction, slider, node, transform) in slider_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        pressed = true;
+        let Some(cursor) = cursor else {
+            continue;
+        };
+
+        // UI transforms are centred on the node, in logical pixels
+        let left = transform.translation().x - node.size().x / 2.0;
+        let volume = ((cursor.x - left) / node.size().x).clamp(0.0, 1.0);
+        let volume = (volume / VOLUME_STEP).round() * VOLUME_STEP;
+        if audio.volume(slider.0) != volume {
+            audio.set_volume(slider.0, volume);
+        }
+    }
+
+    if *dragging && !pressed {
+        audio.save();
+    }
+    *dragging = pressed;
+}
+
+/// Relabels the buttons after a change, including a theme renamed on disk.
+fn update_labels(
+    theme_assets: Res<ThemeAssets>,
+    themes: Res<Assets<Theme>>,
+    theme_settings: Res<ThemeSettings>,
+    accessibility: Res<Accessibility>,
+    death_style: Res<DeathStyle>,
+    audio: Res<AudioSettings>,
+    button_query: Query<(&SettingButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !theme_settings.is_changed()
+        && !themes.is_changed()
+        && !accessibility.is_changed()
+        && !death_style.is_changed()
+        && !audio.is_changed()
+    {
+        return;
+    }
+
+    for (button, children) in button_query.iter() {
+        let label = button.label(
+            &theme_assets,
+            &themes,
+            &theme_settings,
+            &accessibility,
+            &death_style,
+            &audio,
+        );
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = label.clone();
+            }
+        }
+    }
+}
+
+fn update_sliders(
+    audio: Res<AudioSettings>,
+    mut fill_query: Query<(&SliderFill, &mut Style)>,
+    mut label_query: Query<(&VolumeLabel, &mut Text)>,
+) {
+    if !audio.is_changed() {
+        return;
+    }
+
+    for (fill, mut style) in fill_query.iter_mut() {
+        style.width = Val::Percent(audio.volume(fill.0) * 100.0);
+    }
+    for (label, mut text) in label_query.iter_mut() {
+        text.sections[0].value = volume_label(&audio, label.0);
+    }
+}
+
+fn back_to_menu(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if interaction_query
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        next_state.set(GameState::Menu);
+    }
+}
+
+fn cleanup_settings(mut commands: Commands, query: Query<Entity, With<SettingsUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,707 @@
+//! Snake skins, loaded from `.skin` files. A skin colours the first player's
+//! head, body and tail, falling back to the theme's, may draw grid snakes
+//! from a texture atlas and may leave a trail behind. Skins other than the
+//! free ones unlock through high scores or campaign progress, and both the
+//! unlocks and the pick are saved.
+//!
+//! Atlas skins pick a head, straight, corner or tail piece for each segment
+//! from its neighbours; without one the snake keeps its coloured squares.
+
+use std::{collections::BTreeSet, fmt, time::Duration};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use bevy_asset_loader::asset_collection::AssetCollection;
+use rand::random;
+
+use crate::{
+    accessibility::Accessibility,
+    campaign::CampaignProgress,
+    game::{
+        movement_tick, player_colors, Direction, EndlessBest, Player, Position, RoundCleanup, Size,
+        SnakeBody, SnakeHead, SnakeStep,
+    },
+    storage,
+    theme::{Palette, Role},
+    GameState, RoundState,
+};
+
+const SELECTED_KEY: &str = "skins.selected";
+const UNLOCKED_KEY: &str = "skins.unlocked";
+
+const TILE_SIZE: f32 = 16.0;
+const COLUMNS: usize = 4;
+const ROWS: usize = 4;
+
+// atlas layout, each row of pieces follows `piece_offset`
+pub(crate) const HEAD: usize = 0;
+pub(crate) const TAIL: usize = 4;
+const CORNER: usize = 8;
+const VERTICAL: usize = 12;
+pub(crate) const HORIZONTAL: usize = 13;
+
+const TRAIL_TIME: Duration = Duration::from_millis(500);
+const SPARKS: usize = 3;
+
+pub struct SkinPlugin;
+
+impl Plugin for SkinPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Skin>()
+            .init_asset_loader::<SkinLoader>()
+            .insert_resource(SkinProgress::load())
+            .insert_resource(ActiveSkin::default());
+
+        app.add_systems(
+            OnExit(GameState::Loading),
+            (unlock_skins, pick_skin).chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                unlock_skins.run_if(
+                    resource_changed::<EndlessBest>()
+                        .or_else(resource_changed::<CampaignProgress>()),
+                ),
+                pick_skin.run_if(resource_changed::<SkinProgress>()),
+            )
+                .chain()
+                .run_if(resource_exists::<SkinAssets>()),
+        )
+        .add_systems(
+            Update,
+            (
+                lay_trail
+                    .after(SnakeStep)
+                    .run_if(in_state(RoundState::Playing))
+                    .run_if(movement_tick),
+                fade_trail,
+            ),
+        )
+        .add_systems(
+            PostUpdate,
+            (attach_skin, update_skin, remove_skin, paint_squares).chain(),
+        );
+    }
+}
+
+#[derive(AssetCollection, Resource)]
+pub(crate) struct SkinAssets {
+    /// Skins in the order the menu shows them.
+    #[asset(
+        paths(
+            "skins/viper.skin",
+            "skins/classic.skin",
+            "skins/ember.skin",
+            "skins/ghost.skin",
+            "skins/gilded.skin",
+        ),
+        collection(typed)
+    )]
+    pub(crate) skins: Vec<Handle<Skin>>,
+}
+
+#[derive(Asset, TypePath, Debug, Clone)]
+pub(crate) struct Skin {
+    pub(crate) name: String,
+    pub(crate) atlas: Option<Handle<TextureAtlas>>,
+    head: Option<Color>,
+    body: Option<Color>,
+    tail: Option<Color>,
+    trail: Trail,
+    pub(crate) unlock: Unlock,
+}
+
+impl Default for Skin {
+    /// Plain squares in the theme's snake colours.
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            atlas: None,
+            head: None,
+            body: None,
+            tail: None,
+            trail: Trail::None,
+            unlock: Unlock::Free,
+        }
+    }
+}
+
+/// Left behind in each cell the snake leaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Trail {
+    None,
+    /// A ghost of the tail that fades away.
+    Fade,
+    /// A few specks scattered over the cell.
+    Sparkle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Unlock {
+    Free,
+    /// Best classic score reached outside the campaign.
+    Score(usize),
+    /// Campaign levels completed.
+    Levels(usize),
+    /// Stars earned across the campaign.
+    Stars(usize),
+}
+
+impl Unlock {
+    pub(crate) fn label(&self) -> String {
+        match self {
+            Self::Free => "Free".to_string(),
+            Self::Score(score) => format!("Score {score}"),
+            Self::Levels(levels) => format!("Beat {levels} levels"),
+            Self::Stars(stars) => format!("{stars} stars"),
+        }
+    }
+
+    fn is_met(&self, endless_best: usize, progress: &CampaignProgress) -> bool {
+        match *self {
+            Self::Free => true,
+            Self::Score(score) => endless_best >= score,
+            Self::Levels(levels) => progress.completed() >= levels,
+            Self::Stars(stars) => progress.total_stars() >= stars,
+        }
+    }
+}
+
+impl Skin {
+    pub(crate) fn head(&self, palette: &Palette) -> Color {
+        self.head.unwrap_or_else(|| palette.color(Role::SnakeHead))
+    }
+
+    pub(crate) fn body(&self, palette: &Palette) -> Color {
+        self.body.unwrap_or_else(|| palette.color(Role::SnakeBody))
+    }
+
+    /// The tail matches the body unless told otherwise.
+    pub(crate) fn tail(&self, palette: &Palette) -> Color {
+        self.tail.unwrap_or_else(|| self.body(palette))
+    }
+
+    fn parse(source: &str, load_context: &mut LoadContext) -> Result<Self, SkinError> {
+        let mut skin = Skin::default();
+
+        for (number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || SkinError::InvalidEntry(number + 1);
+            let (key, value) = line.split_once(':').ok_or_else(invalid)?;
+            let value = value.trim();
+            let color = || Color::hex(value.trim_start_matches('#')).map_err(|_| invalid());
+
+            match key.trim() {
+                "name" => skin.name = value.to_string(),
+                "atlas" => {
+                    let image = load_context.load(value.to_string());
+                    skin.atlas = Some(load_context.add_labeled_asset(
+                        "atlas".to_string(),
+                        TextureAtlas::from_grid(
+                            image,
+                            Vec2::splat(TILE_SIZE),
+                            COLUMNS,
+                            ROWS,
+                            None,
+                            None,
+                        ),
+                    ));
+                }
+                "head" => skin.head = Some(color()?),
+                "body" => skin.body = Some(color()?),
+                "tail" => skin.tail = Some(color()?),
+                "trail" => {
+                    skin.trail = match value {
+                        "none" => Trail::None,
+                        "fade" => Trail::Fade,
+                        "sparkle" => Trail::Sparkle,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "unlock" => {
+                    skin.unlock = match value.split_once(' ') {
+                        None if value == "free" => Unlock::Free,
+                        Some((kind, amount)) => {
+                            let amount = amount.trim().parse().map_err(|_| invalid())?;
+                            match kind {
+                                "score" => Unlock::Score(amount),
+                                "levels" => Unlock::Levels(amount),
+                                "stars" => Unlock::Stars(amount),
+                                _ => return Err(invalid()),
+                            }
+                        }
+                        None => return Err(invalid()),
+                    }
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        if skin.name.is_empty() {
+            return Err(SkinError::MissingName);
+        }
+        Ok(skin)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum SkinError {
+    Io(std::io::Error),
+    NotUtf8,
+    /// Line number of the malformed entry.
+    InvalidEntry(usize),
+    MissingName,
+}
+
+impl fmt::Display for SkinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read skin: {err}"),
+            Self::NotUtf8 => write!(f, "skin is not valid UTF-8"),
+            Self::InvalidEntry(line) => write!(f, "invalid entry on line {line}"),
+            Self::MissingName => write!(f, "skin has no name"),
+        }
+    }
+}
+
+impl std::error::Error for SkinError {}
+
+impl From<std::io::Error> for SkinError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+#[derive(Default)]
+struct SkinLoader;
+
+impl AssetLoader for SkinLoader {
+    type Asset = Skin;
+    type Settings = ();
+    type Error = SkinError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Skin, SkinError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let source = std::str::from_utf8(&bytes).map_err(|_| SkinError::NotUtf8)?;
+            Skin::parse(source, load_context)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["skin"]
+    }
+}
+
+/// Names of the unlocked skins and of the one picked in the menu.
+#[derive(Resource, Default)]
+pub(crate) struct SkinProgress {
+    pub(crate) selected: String,
+    unlocked: BTreeSet<String>,
+}
+
+impl SkinProgress {
+    fn load() -> Self {
+        Self {
+            selected: storage::load(SELECTED_KEY).unwrap_or_default(),
+            unlocked: storage::load(UNLOCKED_KEY)
+                .map(|source| source.lines().map(str::to_string).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn save(&self) {
+        let unlocked = self.unlocked.iter().cloned().collect::<Vec<_>>().join("\n");
+        for (key, value) in [(SELECTED_KEY, &self.selected), (UNLOCKED_KEY, &unlocked)] {
+            if let Err(err) = storage::save(key, value) {
+                warn!("Could not save skins: {err}");
+            }
+        }
+    }
+
+    pub(crate) fn is_unlocked(&self, skin: &Skin) -> bool {
+        skin.unlock == Unlock::Free || self.unlocked.contains(&skin.name)
+    }
+
+    /// Picks an unlocked skin, returns whether it was.
+    pub(crate) fn select(&mut self, skin: &Skin) -> bool {
+        if !self.is_unlocked(skin) {
+            return false;
+        }
+        if self.selected != skin.name {
+            self.selected = skin.name.clone();
+            self.save();
+        }
+        true
+    }
+}
+
+/// Skin the first player wears, an unloaded handle means plain squares.
+#[derive(Resource, Default)]
+struct ActiveSkin(Handle<Skin>);
+
+fn unlock_skins(
+    skin_assets: Res<SkinAssets>,
+    skins: Res<Assets<Skin>>,
+    endless_best: Res<EndlessBest>,
+    campaign: Res<CampaignProgress>,
+    mut progress: ResMut<SkinProgress>,
+) {
+    let unlocked = skin_assets
+        .skins
+        .iter()
+        .filter_map(|handle| skins.get(handle))
+        .filter(|skin| !progress.is_unlocked(skin))
+        .filter(|skin| skin.unlock.is_met(endless_best.0, &campaign))
+        .map(|skin| skin.name.clone())
+        .collect::<Vec<_>>();
+    if unlocked.is_empty() {
+        return;
+    }
+
+    for name in unlocked {
+        info!("Unlocked skin: {name}");
+        progress.unlocked.insert(name);
+    }
+    progress.save();
+}
+
+/// Wears the selected skin, or the first one if it is unknown or locked.
+fn pick_skin(
+    skin_assets: Res<SkinAssets>,
+    skins: Res<Assets<Skin>>,
+    progress: Res<SkinProgress>,
+    mut active: ResMut<ActiveSkin>,
+) {
+    let wearable = |handle: &&Handle<Skin>| {
+        skins
+            .get(*handle)
+            .is_some_and(|skin| progress.is_unlocked(skin))
+    };
+    let picked = skin_assets
+        .skins
+        .iter()
+        .filter(wearable)
+        .find(|handle| {
+            skins
+                .get(*handle)
+                .is_some_and(|skin| skin.name == progress.selected)
+        })
+        .or_else(|| skin_assets.skins.iter().find(wearable));
+    active.0 = picked.cloned().unwrap_or_default();
+}
+
+/// Order of directions within the head, tail and corner rows. Corners are
+/// named by the first of the two sides they join, going clockwise.
+pub(crate) fn piece_offset(direction: Direction) -> usize {
+    match direction {
+        Direction::Up => 0,
+        Direction::Right => 1,
+        Direction::Down => 2,
+        Direction::Left => 3,
+    }
+}
+
+/// Direction of the single step leading from `from` to `to`, if they are
+/// neighbours; segments either side of a portal are not.
+fn step_direction(from: &Position, to: &Position) -> Option<Direction> {
+    match (to.x - from.x, to.y - from.y) {
+        (-1, 0) => Some(Direction::Left),
+        (1, 0) => Some(Direction::Right),
+        (0, 1) => Some(Direction::Up),
+        (0, -1) => Some(Direction::Down),
+        _ => None,
+    }
+}
+
+/// Atlas index of a segment joining the sides `a` and `b`.
+fn joint_index(a: Direction, b: Direction) -> usize {
+    match (a, b) {
+        (Direction::Up | Direction::Down, Direction::Up | Direction::Down) => VERTICAL,
+        (Direction::Left | Direction::Right, Direction::Left | Direction::Right) => HORIZONTAL,
+        _ => {
+            let (a, b) = (piece_offset(a), piece_offset(b));
+            // the side the corner turns clockwise from
+            let first = if (a + 1) % 4 == b { a } else { b };
+            CORNER + first
+        }
+    }
+}
+
+/// Colour of a segment: the first player wears the skin's, the others their
+/// own head and body colours so they stay apart.
+fn segment_color(
+    skin: &Skin,
+    palette: &Palette,
+    player: &Player,
+    index: usize,
+    len: usize,
+) -> Color {
+    if player.0 != 0 {
+        let (head, body) = player_colors(player.0, palette);
+        return if index == 0 { head } else { body };
+    }
+    match index {
+        0 => skin.head(palette),
+        _ if index + 1 == len => skin.tail(palette),
+        _ => skin.body(palette),
+    }
+}
+
+/// Skin sprite drawn over a segment, whose own square is then see-through.
+#[derive(Component)]
+struct SkinSprite;
+
+/// Segment currently drawn with the skin sprite child.
+#[derive(Component)]
+struct Skinned;
+
+fn attach_skin(
+    mut commands: Commands,
+    active: Res<ActiveSkin>,
+    skins: Res<Assets<Skin>>,
+    snake_query: Query<&SnakeBody, With<SnakeHead>>,
+    segment_query: Query<(), Without<Skinned>>,
+    mut sprite_query: Query<&mut Sprite>,
+) {
+    let Some(atlas) = skins.get(&active.0).and_then(|skin| skin.atlas.clone()) else {
+        return;
+    };
+
+    for body in snake_query.iter() {
+        for segment in body.iter() {
+            if !segment_query.contains(*segment) {
+                continue;
+            }
+            if let Ok(mut sprite) = sprite_query.get_mut(*segment) {
+                sprite.color.set_a(0.0);
+            }
+            let child = commands
+                .spawn((
+                    SpriteSheetBundle {
+                        sprite: TextureAtlasSprite {
+                            custom_size: Some(Vec2::ONE),
+                            ..default()
+                        },
+                        texture_atlas: atlas.clone(),
+                        // just above the segment it covers
+                        transform: Transform::from_xyz(0.0, 0.0, 0.01),
+                        ..default()
+                    },
+                    SkinSprite,
+                ))
+                .id();
+            commands.entity(*segment).insert(Skinned).add_child(child);
+        }
+    }
+}
+
+fn update_skin(
+    active: Res<ActiveSkin>,
+    skins: Res<Assets<Skin>>,
+    palette: Res<Palette>,
+    snake_query: Query<(&SnakeHead, &Player, &SnakeBody)>,
+    segment_query: Query<(&Position, &Size, &Children), With<Skinned>>,
+    mut skin_query: Query<(&mut TextureAtlasSprite, &mut Transform)>,
+) {
+    let Some(skin) = skins.get(&active.0) else {
+        return;
+    };
+
+    for (head, player, body) in snake_query.iter() {
+        let positions = body
+            .iter()
+            .map(|segment| segment_query.get(*segment).ok())
+            .collect::<Vec<_>>();
+
+        for (index, segment) in positions.iter().enumerate() {
+            let Some((pos, size, children)) = segment else {
+                continue;
+            };
+            let towards = |other: Option<usize>| {
+                other
+                    .and_then(|other| positions.get(other))
+                    .and_then(|other| other.as_ref())
+                    .and_then(|(other, _, _)| step_direction(pos, other))
+            };
+            let ahead = index.checked_sub(1);
+            let behind = Some(index + 1).filter(|behind| *behind < positions.len());
+
+            let atlas_index = match (towards(ahead), towards(behind)) {
+                (Some(a), Some(b)) => joint_index(a, b),
+                // the tail points away from the segment ahead of it
+                (Some(a), None) if behind.is_none() => TAIL + piece_offset(a.opposite()),
+                (None, Some(b)) if ahead.is_none() => HEAD + piece_offset(b.opposite()),
+                (None, None) if ahead.is_none() => HEAD + piece_offset(head.direction()),
+                (Some(side), None) | (None, Some(side)) => joint_index(side, side.opposite()),
+                (None, None) => joint_index(head.direction(), head.direction()),
+            };
+            let color = segment_color(skin, &palette, player, index, positions.len());
+
+            for child in children.iter() {
+                let Ok((mut sprite, mut child_transform)) = skin_query.get_mut(*child) else {
+                    continue;
+                };
+                if sprite.index != atlas_index || sprite.color != color {
+                    sprite.index = atlas_index;
+                    sprite.color = color;
+                }
+                // a whole cell, whatever share of it the segment's square covers
+                child_transform.scale = Vec3::new(
+                    1.0 / size.width.max(f32::EPSILON),
+                    1.0 / size.height.max(f32::EPSILON),
+                    1.0,
+                );
+            }
+        }
+    }
+}
+
+/// Drops the skin sprite from pieces no longer part of a snake, such as
+/// segments cut off into food, and from every segment once the skin worn has
+/// no atlas.
+fn remove_skin(
+    mut commands: Commands,
+    active: Res<ActiveSkin>,
+    skins: Res<Assets<Skin>>,
+    body_query: Query<&SnakeBody>,
+    mut skinned_query: Query<(Entity, &Children, Option<&mut Sprite>), With<Skinned>>,
+    skin_sprite_query: Query<(), With<SkinSprite>>,
+) {
+    let atlas = skins
+        .get(&active.0)
+        .is_some_and(|skin| skin.atlas.is_some());
+
+    for (segment, children, sprite) in skinned_query.iter_mut() {
+        if atlas && body_query.iter().any(|body| body.contains(&segment)) {
+            continue;
+        }
+        for child in children.iter() {
+            if skin_sprite_query.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+        if let Some(mut sprite) = sprite {
+            sprite.color.set_a(1.0);
+        }
+        commands.entity(segment).remove::<Skinned>();
+    }
+}
+
+/// Colours the first player's squares when the skin has no atlas, in the
+/// theme's colours until a skin is picked.
+fn paint_squares(
+    active: Res<ActiveSkin>,
+    skins: Res<Assets<Skin>>,
+    palette: Res<Palette>,
+    snake_query: Query<(&Player, &SnakeBody)>,
+    mut sprite_query: Query<&mut Sprite, Without<Skinned>>,
+) {
+    let fallback = Skin::default();
+    let skin = skins.get(&active.0).unwrap_or(&fallback);
+
+    for (player, body) in snake_query.iter().filter(|(player, _)| player.0 == 0) {
+        for (index, segment) in body.iter().enumerate() {
+            let Ok(mut sprite) = sprite_query.get_mut(*segment) else {
+                continue;
+            };
+            let color = segment_color(skin, &palette, player, index, body.len());
+            if sprite.color != color {
+                sprite.color = color;
+            }
+        }
+    }
+}
+
+/// Fading mark left in a cell the first player's snake moved out of.
+#[derive(Component)]
+struct TrailMark(Timer);
+
+fn lay_trail(
+    mut commands: Commands,
+    active: Res<ActiveSkin>,
+    skins: Res<Assets<Skin>>,
+    palette: Res<Palette>,
+    accessibility: Res<Accessibility>,
+    head_query: Query<(&SnakeHead, &Player)>,
+) {
+    let Some(skin) = skins
+        .get(&active.0)
+        .filter(|skin| skin.trail != Trail::None)
+    else {
+        return;
+    };
+
+    for (head, _) in head_query.iter().filter(|(_, player)| player.0 == 0) {
+        let Some(tail) = head.tail() else {
+            continue;
+        };
+        // children are laid out in cell units, the mark is scaled to a cell
+        let marks = match skin.trail {
+            // sparkles are particles, reduced motion leaves a plain mark
+            Trail::Sparkle if !accessibility.reduced_motion => (0..SPARKS)
+                .map(|_| {
+                    (
+                        Vec2::new(random::<f32>(), random::<f32>()) * 0.8 - 0.4,
+                        0.15,
+                    )
+                })
+                .collect(),
+            _ => vec![(Vec2::ZERO, 0.6)],
+        };
+
+        commands
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, -0.2)),
+                TrailMark(Timer::new(TRAIL_TIME, TimerMode::Once)),
+                tail,
+                Size::square(1.0),
+                RoundCleanup,
+            ))
+            .with_children(|parent| {
+                for (offset, size) in marks {
+                    parent.spawn(SpriteBundle {
+                        sprite: Sprite {
+                            color: skin.tail(&palette),
+                            custom_size: Some(Vec2::splat(size)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(offset.extend(0.0)),
+                        ..default()
+                    });
+                }
+            });
+    }
+}
+
+fn fade_trail(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut trail_query: Query<(Entity, &mut TrailMark, &Children)>,
+    mut sprite_query: Query<&mut Sprite>,
+) {
+    for (entity, mut mark, children) in trail_query.iter_mut() {
+        if mark.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        for child in children.iter() {
+            if let Ok(mut sprite) = sprite_query.get_mut(*child) {
+                sprite.color.set_a(mark.0.percent_left() * 0.6);
+            }
+        }
+    }
+}
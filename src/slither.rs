@@ -0,0 +1,372 @@
+//! Slither mode: the head steers freely at a steady speed instead of moving
+//! cell by cell, and the body follows the path it leaves. Collisions with
+//! food, walls, portals and its own body come from bevy_xpbd_2d sensors.
+
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_xpbd_2d::prelude::{Collider, CollisionStarted, Sensor};
+
+use crate::{
+    game::{
+        arena_to_world, Direction, Food, GameOverEvent, GrowthEvent, Player, Position,
+        RoundCleanup, RoundSetup, Size, SnakeBody, SpeedLevel,
+    },
+    level::{Arena, Portal, Wall},
+    theme::{Palette, Role},
+    GameMode, GameState, RoundState,
+};
+
+/// Cells per second.
+const SLITHER_SPEED: f32 = 3.0;
+const SPEED_PER_LEVEL: f32 = 0.3;
+const BOOST_MULTIPLIER: f32 = 1.8;
+/// Radians per second.
+const TURN_RATE: f32 = 4.0;
+/// Distance between two consecutive segments, in cells.
+const SEGMENT_SPACING: f32 = 0.45;
+const INITIAL_SEGMENTS: usize = 3;
+const SEGMENTS_PER_FOOD: usize = 2;
+/// The first segments always overlap the head and never count as a bite.
+const SELF_COLLISION_GRACE: usize = 3;
+/// How far past the exit portal's centre the head comes out, far enough not to
+/// touch it again.
+const PORTAL_EXIT_DISTANCE: f32 = 1.0;
+/// Trail steps longer than this are portal jumps and take no length.
+const MAX_TRAIL_STEP: f32 = 1.0;
+
+pub struct SlitherPlugin;
+
+impl Plugin for SlitherPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SlitherTrail::default())
+            .insert_resource(PendingSegments(0));
+
+        app.add_systems(
+            OnEnter(GameState::Game),
+            setup
+                .in_set(RoundSetup::Spawn)
+                .run_if(resource_equals(GameMode::Slither)),
+        );
+
+        app.add_systems(
+            Update,
+            (
+                slither_movement,
+                add_colliders,
+                slither_collisions.after(slither_movement),
+                slither_growth.after(slither_collisions),
+            )
+                .run_if(in_state(RoundState::Playing))
+                .run_if(resource_equals(GameMode::Slither)),
+        )
+        .add_systems(PostUpdate, free_position_translation);
+    }
+}
+
+/// Continuous position in arena coordinates, where whole numbers are cell centres.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub(crate) struct FreePosition(pub(crate) Vec2);
+
+#[derive(Component)]
+struct SlitherHead {
+    angle: f32,
+}
+
+#[derive(Component)]
+struct SlitherSegment;
+
+/// Recent head positions, newest first; the body is laid out along it.
+/// Portals leave a gap between two points that the body skips over.
+#[derive(Resource, Default)]
+struct SlitherTrail(VecDeque<Vec2>);
+
+/// Segments still to be added to the tail after eating.
+#[derive(Resource)]
+struct PendingSegments(usize);
+
+fn setup(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    mut trail: ResMut<SlitherTrail>,
+    mut pending: ResMut<PendingSegments>,
+    arena: Res<Arena>,
+) {
+    let start = Vec2::new(arena.spawn.x as f32, arena.spawn.y as f32);
+    let angle = match arena.facing {
+        Direction::Right => 0.0,
+        Direction::Up => std::f32::consts::FRAC_PI_2,
+        Direction::Left => std::f32::consts::PI,
+        Direction::Down => -std::f32::consts::FRAC_PI_2,
+    };
+
+    trail.0 = VecDeque::from([start]);
+    pending.0 = 0;
+
+    let head = commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: palette.color(Role::SnakeHead),
+                    ..default()
+                },
+                ..default()
+            },
+            SlitherHead { angle },
+            Player(0),
+            FreePosition(start),
+            Size::square(0.8),
+            Collider::ball(0.5),
+            Sensor,
+            RoundCleanup,
+        ))
+        .id();
+
+    let mut body = SnakeBody(vec![head]);
+    for i in 1..=INITIAL_SEGMENTS {
+        let position = start - Vec2::from_angle(angle) * SEGMENT_SPACING * i as f32;
+        trail.0.push_back(position);
+        body.push(spawn_segment(&mut commands, position, &palette));
+    }
+    commands.entity(head).insert(body);
+
+    // walls just outside the arena
+    let width = arena.width as f32;
+    let height = arena.height as f32;
+    let walls = [
+        (
+            Vec2::new(-1.0, height / 2.0 - 0.5),
+            Vec2::new(1.0, height + 2.0),
+        ),
+        (
+            Vec2::new(width, height / 2.0 - 0.5),
+            Vec2::new(1.0, height + 2.0),
+        ),
+        (
+            Vec2::new(width / 2.0 - 0.5, -1.0),
+            Vec2::new(width + 2.0, 1.0),
+        ),
+        (
+            Vec2::new(width / 2.0 - 0.5, height),
+            Vec2::new(width + 2.0, 1.0),
+        ),
+    ];
+    for (center, size) in walls {
+        commands.spawn((
+            SpatialBundle::default(),
+            Wall,
+            FreePosition(center),
+            Size {
+                width: size.x,
+                height: size.y,
+            },
+            Collider::cuboid(1.0, 1.0),
+            Sensor,
+            RoundCleanup,
+        ));
+    }
+}
+
+fn spawn_segment(commands: &mut Commands, position: Vec2, palette: &Palette) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: palette.color(Role::SnakeBody),
+                    ..default()
+                },
+                ..default()
+            },
+            SlitherSegment,
+            FreePosition(position),
+            Size::square(0.6),
+            Collider::ball(0.5),
+            Sensor,
+            RoundCleanup,
+        ))
+        .id()
+}
+
+fn slither_movement(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    speed_level: Res<SpeedLevel>,
+    mut trail: ResMut<SlitherTrail>,
+    mut head_query: Query<(Entity, &mut SlitherHead, &SnakeBody, &mut Transform)>,
+    mut positions_query: Query<&mut FreePosition>,
+) {
+    let Ok((head_entity, mut head, body, mut transform)) = head_query.get_single_mut() else {
+        return;
+    };
+
+    let mut turn = 0.0;
+    if keyboard_input.any_pressed([KeyCode::Left, KeyCode::A]) {
+        turn += 1.0;
+    }
+    if keyboard_input.any_pressed([KeyCode::Right, KeyCode::D]) {
+        turn -= 1.0;
+    }
+    head.angle += turn * TURN_RATE * time.delta_seconds();
+    transform.rotation = Quat::from_rotation_z(head.angle);
+
+    let mut speed = SLITHER_SPEED + SPEED_PER_LEVEL * (speed_level.0 - 1) as f32;
+    if keyboard_input.any_pressed([KeyCode::Space, KeyCode::Up, KeyCode::W]) {
+        speed *= BOOST_MULTIPLIER;
+    }
+
+    let Ok(mut head_pos) = positions_query.get_mut(head_entity) else {
+        return;
+    };
+    head_pos.0 += Vec2::from_angle(head.angle) * speed * time.delta_seconds();
+    trail.0.push_front(head_pos.0);
+
+    // lay every segment out along the trail, SEGMENT_SPACING apart
+    let mut points = trail.0.iter().copied();
+    let mut previous = points.next().unwrap_or(head_pos.0);
+    let mut walked = 0.0;
+    let mut used = 1;
+    for (i, segment) in body.iter().enumerate().skip(1) {
+        let target = SEGMENT_SPACING * i as f32;
+        let mut position = previous;
+        for point in points.by_ref() {
+            used += 1;
+            let step = match previous.distance(point) {
+                step if step > MAX_TRAIL_STEP => 0.0,
+                step => step,
+            };
+            if walked + step >= target {
+                let t = if step > 0.0 {
+                    (target - walked) / step
+                } else {
+                    0.0
+                };
+                position = previous.lerp(point, t);
+                walked = target;
+                previous = position;
+                break;
+            }
+            walked += step;
+            previous = point;
+            position = point;
+        }
+
+        if let Ok(mut segment_pos) = positions_query.get_mut(*segment) {
+            segment_pos.0 = position;
+        }
+    }
+
+    // the lerped point replaced part of a step, keep one spare point for the tail
+    trail.0.truncate(used + 1);
+}
+
+/// Grid-based food and level tiles only get colliders in this mode.
+fn add_colliders(
+    mut commands: Commands,
+    food_query: Query<Entity, Added<Food>>,
+    wall_query: Query<Entity, (Added<Wall>, Without<Collider>)>,
+    portal_query: Query<Entity, Added<Portal>>,
+) {
+    for food in food_query.iter().chain(portal_query.iter()) {
+        commands.entity(food).insert((Collider::ball(0.5), Sensor));
+    }
+    for wall in wall_query.iter() {
+        commands
+            .entity(wall)
+            .insert((Collider::cuboid(1.0, 1.0), Sensor));
+    }
+}
+
+fn slither_collisions(
+    mut commands: Commands,
+    arena: Res<Arena>,
+    mut trail: ResMut<SlitherTrail>,
+    mut collision_events: EventReader<CollisionStarted>,
+    mut head_query: Query<(&SlitherHead, &SnakeBody, &mut FreePosition)>,
+    food_query: Query<(), With<Food>>,
+    portal_query: Query<&Position, With<Portal>>,
+    wall_query: Query<(), With<Wall>>,
+    segment_query: Query<(), With<SlitherSegment>>,
+    mut growth_ev_writer: EventWriter<GrowthEvent>,
+    mut game_over_ev_writer: EventWriter<GameOverEvent>,
+) {
+    for CollisionStarted(first, second) in collision_events.read() {
+        let (head_entity, other) = if head_query.contains(*first) {
+            (*first, *second)
+        } else if head_query.contains(*second) {
+            (*second, *first)
+        } else {
+            continue;
+        };
+        let Ok((head, body, mut head_pos)) = head_query.get_mut(head_entity) else {
+            continue;
+        };
+
+        if food_query.contains(other) {
+            commands.entity(other).despawn();
+            growth_ev_writer.send(GrowthEvent(head_entity));
+        } else if let Ok(portal) = portal_query.get(other) {
+            let Some(exit) = arena.portal_exit(portal) else {
+                continue;
+            };
+            head_pos.0 = Vec2::new(exit.x as f32, exit.y as f32)
+                + Vec2::from_angle(head.angle) * PORTAL_EXIT_DISTANCE;
+            trail.0.push_front(head_pos.0);
+        } else if wall_query.contains(other)
+            || segment_query.contains(other)
+                && body
+                    .iter()
+                    .position(|segment| *segment == other)
+                    .is_some_and(|index| index > SELF_COLLISION_GRACE)
+        {
+            game_over_ev_writer.send(GameOverEvent(head_entity));
+        }
+    }
+}
+
+fn slither_growth(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    mut body_query: Query<&mut SnakeBody, With<SlitherHead>>,
+    mut pending: ResMut<PendingSegments>,
+    mut growth_ev_reader: EventReader<GrowthEvent>,
+    positions_query: Query<&FreePosition>,
+) {
+    for _ in growth_ev_reader.read() {
+        pending.0 += SEGMENTS_PER_FOOD;
+    }
+
+    if pending.0 == 0 {
+        return;
+    }
+    let Ok(mut body) = body_query.get_single_mut() else {
+        return;
+    };
+
+    let Some(tail) = body
+        .last()
+        .and_then(|tail| positions_query.get(*tail).ok())
+        .copied()
+    else {
+        return;
+    };
+
+    // one segment per frame, they spread out along the trail as the snake moves
+    let segment = spawn_segment(&mut commands, tail.0, &palette);
+    body.push(segment);
+    pending.0 -= 1;
+}
+
+fn free_position_translation(
+    arena: Res<Arena>,
+    primary_query: Query<&Window, With<PrimaryWindow>>,
+    mut query: Query<(&FreePosition, &mut Transform)>,
+) {
+    let Ok(window) = primary_query.get_single() else {
+        return;
+    };
+
+    for (position, mut transform) in query.iter_mut() {
+        transform.translation =
+            arena_to_world(position.0, window, &arena).extend(transform.translation.z);
+    }
+}
@@ -0,0 +1,41 @@
+//! Small key/value persistence for settings and progress: plain files next to
+//! the game on native builds, local storage on the web.
+
+#[cfg(not(target_arch = "wasm32"))]
+const SAVE_DIR: &str = "saves";
+#[cfg(target_arch = "wasm32")]
+const KEY_PREFIX: &str = "snake.";
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn load(key: &str) -> Option<String> {
+    std::fs::read_to_string(std::path::Path::new(SAVE_DIR).join(key)).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn save(key: &str, value: &str) -> Result<(), String> {
+    std::fs::create_dir_all(SAVE_DIR).map_err(|err| err.to_string())?;
+    std::fs::write(std::path::Path::new(SAVE_DIR).join(key), value).map_err(|err| err.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, String> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| "local storage is unavailable".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn load(key: &str) -> Option<String> {
+    local_storage()
+        .ok()?
+        .get_item(&format!("{KEY_PREFIX}{key}"))
+        .ok()
+        .flatten()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn save(key: &str, value: &str) -> Result<(), String> {
+    local_storage()?
+        .set_item(&format!("{KEY_PREFIX}{key}"), value)
+        .map_err(|_| "local storage is full".to_string())
+}
@@ -0,0 +1,566 @@
+//! Colour themes, loaded from `.theme` files. The theme picked in the settings
+//! is copied into the `Palette` resource, which spawners read; entities tagged
+//! `Themed` are repainted whenever it changes. Native builds reload the theme
+//! file when it is edited on disk.
+
+use std::{collections::HashMap, fmt};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+    window::PrimaryWindow,
+};
+use bevy_asset_loader::asset_collection::AssetCollection;
+
+use crate::{
+    game::{arena_to_world, tile_size, RoundCleanup, RoundSetup},
+    hex::hex_round,
+    level::Arena,
+    storage, GameState,
+};
+
+const THEME_KEY: &str = "settings.theme";
+/// Share of a cell covered by its tile on grid floors, the gaps show the
+/// background.
+const GRID_TILE: f32 = 0.92;
+/// Hovered buttons fade to this alpha.
+const HOVER_ALPHA: f32 = 0.5;
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Theme>()
+            .init_asset_loader::<ThemeLoader>()
+            .insert_resource(ThemeSettings::load())
+            .insert_resource(Palette::default())
+            .insert_resource(ClearColor(Palette::default().color(Role::Background)));
+
+        app.add_systems(OnExit(GameState::Loading), pick_theme)
+            .add_systems(
+                Update,
+                (
+                    pick_theme.run_if(
+                        resource_changed::<ThemeSettings>()
+                            .or_else(on_event::<AssetEvent<Theme>>()),
+                    ),
+                    highlight_buttons,
+                )
+                    .run_if(resource_exists::<ThemeAssets>()),
+            )
+            .add_systems(
+                OnEnter(GameState::Game),
+                spawn_floor.in_set(RoundSetup::Spawn).run_if(not(hex_round)),
+            )
+            .add_systems(
+                Update,
+                (despawn_floor, spawn_floor)
+                    .chain()
+                    .run_if(resource_changed::<Palette>())
+                    .run_if(in_state(GameState::Game))
+                    .run_if(not(hex_round)),
+            )
+            .add_systems(PostUpdate, (paint_themed, position_floor));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        app.insert_resource(ThemeWatch::default())
+            .add_systems(Update, watch_theme.run_if(resource_exists::<ThemeAssets>()));
+    }
+}
+
+#[derive(AssetCollection, Resource)]
+pub(crate) struct ThemeAssets {
+    /// Themes in the order the settings cycle through them.
+    #[asset(
+        paths(
+            "themes/classic.theme",
+            "themes/midnight.theme",
+            "themes/forest.theme",
+            "themes/paper.theme",
+            "themes/high_contrast.theme",
+        ),
+        collection(typed)
+    )]
+    pub(crate) themes: Vec<Handle<Theme>>,
+}
+
+/// What a colour is used for; theme files name them by `key`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Role {
+    Background,
+    Floor,
+    FloorAlt,
+    /// Cells of the hex board and of the editor grid.
+    Tile,
+    Wall,
+    Food,
+    SnakeHead,
+    SnakeBody,
+    HazardSaw,
+    HazardSlider,
+    HazardPulse,
+    /// Cells about to turn into wall in battle royale.
+    Warning,
+    /// HUD bar, editor toolbar and minimap.
+    Panel,
+    Letterbox,
+    Text,
+    TextMuted,
+    TextAccent,
+    Button,
+    ButtonPrimary,
+    ButtonSecondary,
+    ButtonTertiary,
+    ButtonDanger,
+    ButtonDisabled,
+}
+
+impl Role {
+    const ALL: [Self; 23] = [
+        Self::Background,
+        Self::Floor,
+        Self::FloorAlt,
+        Self::Tile,
+        Self::Wall,
+        Self::Food,
+        Self::SnakeHead,
+        Self::SnakeBody,
+        Self::HazardSaw,
+        Self::HazardSlider,
+        Self::HazardPulse,
+        Self::Warning,
+        Self::Panel,
+        Self::Letterbox,
+        Self::Text,
+        Self::TextMuted,
+        Self::TextAccent,
+        Self::Button,
+        Self::ButtonPrimary,
+        Self::ButtonSecondary,
+        Self::ButtonTertiary,
+        Self::ButtonDanger,
+        Self::ButtonDisabled,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Background => "background",
+            Self::Floor => "floor",
+            Self::FloorAlt => "floor_alt",
+            Self::Tile => "tile",
+            Self::Wall => "wall",
+            Self::Food => "food",
+            Self::SnakeHead => "snake_head",
+            Self::SnakeBody => "snake_body",
+            Self::HazardSaw => "hazard_saw",
+            Self::HazardSlider => "hazard_slider",
+            Self::HazardPulse => "hazard_pulse",
+            Self::Warning => "warning",
+            Self::Panel => "panel",
+            Self::Letterbox => "letterbox",
+            Self::Text => "text",
+            Self::TextMuted => "text_muted",
+            Self::TextAccent => "text_accent",
+            Self::Button => "button",
+            Self::ButtonPrimary => "button_primary",
+            Self::ButtonSecondary => "button_secondary",
+            Self::ButtonTertiary => "button_tertiary",
+            Self::ButtonDanger => "button_danger",
+            Self::ButtonDisabled => "button_disabled",
+        }
+    }
+
+    /// Colours used before any theme loads and for keys a theme leaves out.
+    fn default_color(&self) -> Color {
+        match self {
+            Self::Background | Self::Floor => Color::BLACK,
+            Self::FloorAlt => Color::rgb(0.05, 0.05, 0.05),
+            Self::Tile => Color::rgb(0.12, 0.12, 0.12),
+            Self::Wall => Color::rgb(0.45, 0.3, 0.2),
+            Self::Food => Color::GREEN,
+            Self::SnakeHead => Color::WHITE,
+            Self::SnakeBody => Color::rgb(0.3, 0.3, 0.3),
+            Self::HazardSaw => Color::RED,
+            Self::HazardSlider => Color::MAROON,
+            Self::HazardPulse => Color::ORANGE,
+            Self::Warning => Color::rgba(1.0, 0.1, 0.1, 0.5),
+            Self::Panel => Color::rgb(0.1, 0.1, 0.1),
+            Self::Letterbox => Color::rgb(0.05, 0.05, 0.05),
+            Self::Text => Color::WHITE,
+            Self::TextMuted => Color::GRAY,
+            Self::TextAccent => Color::YELLOW,
+            Self::Button => Color::MIDNIGHT_BLUE,
+            Self::ButtonPrimary => Color::VIOLET,
+            Self::ButtonSecondary => Color::TEAL,
+            Self::ButtonTertiary => Color::SEA_GREEN,
+            Self::ButtonDanger => Color::ORANGE_RED,
+            Self::ButtonDisabled => Color::DARK_GRAY,
+        }
+    }
+}
+
+/// How the arena floor is drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum FloorStyle {
+    /// Just the background.
+    #[default]
+    Plain,
+    /// Cells alternate between the two floor colours.
+    Checker,
+    /// Floor tiles with the background showing between them.
+    Grid,
+}
+
+#[derive(Asset, TypePath, Debug, Clone, Default, PartialEq)]
+pub(crate) struct Theme {
+    pub(crate) name: String,
+    pub(crate) floor: FloorStyle,
+    colors: HashMap<Role, Color>,
+}
+
+impl Theme {
+    pub(crate) fn color(&self, role: Role) -> Color {
+        self.colors
+            .get(&role)
+            .copied()
+            .unwrap_or_else(|| role.default_color())
+    }
+
+    fn parse(source: &str) -> Result<Self, ThemeError> {
+        let mut theme = Theme::default();
+
+        for (number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || ThemeError::InvalidEntry(number + 1);
+            let (key, value) = line.split_once(':').ok_or_else(invalid)?;
+            let (key, value) = (key.trim(), value.trim());
+
+            match key {
+                "name" => theme.name = value.to_string(),
+                "style" => {
+                    theme.floor = match value {
+                        "plain" => FloorStyle::Plain,
+                        "checker" => FloorStyle::Checker,
+                        "grid" => FloorStyle::Grid,
+                        _ => return Err(invalid()),
+                    }
+                }
+                _ => {
+                    let role = Role::ALL
+                        .into_iter()
+                        .find(|role| role.key() == key)
+                        .ok_or_else(invalid)?;
+                    let color = Color::hex(value.trim_start_matches('#')).map_err(|_| invalid())?;
+                    theme.colors.insert(role, color);
+                }
+            }
+        }
+
+        if theme.name.is_empty() {
+            return Err(ThemeError::MissingName);
+        }
+        Ok(theme)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum ThemeError {
+    Io(std::io::Error),
+    NotUtf8,
+    /// Line number of the malformed entry.
+    InvalidEntry(usize),
+    MissingName,
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read theme: {err}"),
+            Self::NotUtf8 => write!(f, "theme is not valid UTF-8"),
+            Self::InvalidEntry(line) => write!(f, "invalid entry on line {line}"),
+            Self::MissingName => write!(f, "theme has no name"),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+impl From<std::io::Error> for ThemeError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+#[derive(Default)]
+struct ThemeLoader;
+
+impl AssetLoader for ThemeLoader {
+    type Asset = Theme;
+    type Settings = ();
+    type Error = ThemeError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Theme, ThemeError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let source = std::str::from_utf8(&bytes).map_err(|_| ThemeError::NotUtf8)?;
+            Theme::parse(source)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme"]
+    }
+}
+
+/// Colours in use, copied from the picked theme.
+#[derive(Resource, Default, Deref)]
+pub(crate) struct Palette(Theme);
+
+/// Name of the theme picked in the settings.
+#[derive(Resource, Default)]
+pub(crate) struct ThemeSettings {
+    pub(crate) selected: String,
+}
+
+impl ThemeSettings {
+    fn load() -> Self {
+        Self {
+            selected: storage::load(THEME_KEY).unwrap_or_default(),
+        }
+    }
+
+    pub(crate) fn select(&mut self, theme: &Theme) {
+        self.selected = theme.name.clone();
+        if let Err(err) = storage::save(THEME_KEY, &self.selected) {
+            warn!("Could not save the theme: {err}");
+        }
+    }
+}
+
+/// Takes its colour from the palette, and again whenever the palette changes.
+/// Works on sprites, UI backgrounds and text.
+#[derive(Component, Clone, Copy)]
+pub(crate) struct Themed(pub(crate) Role);
+
+/// Handle of the selected theme, or the first one if it is unknown.
+pub(crate) fn selected_theme<'a>(
+    theme_assets: &'a ThemeAssets,
+    themes: &Assets<Theme>,
+    settings: &ThemeSettings,
+) -> Option<&'a Handle<Theme>> {
+    theme_assets
+        .themes
+        .iter()
+        .find(|handle| {
+            themes
+                .get(*handle)
+                .is_some_and(|theme| theme.name == settings.selected)
+        })
+        .or_else(|| theme_assets.themes.first())
+}
+
+fn pick_theme(
+    theme_assets: Res<ThemeAssets>,
+    themes: Res<Assets<Theme>>,
+    settings: Res<ThemeSettings>,
+    mut palette: ResMut<Palette>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    let Some(theme) =
+        selected_theme(&theme_assets, &themes, &settings).and_then(|handle| themes.get(handle))
+    else {
+        return;
+    };
+    // reloads of other themes change nothing
+    if palette.0 == *theme {
+        return;
+    }
+
+    palette.0 = theme.clone();
+    clear_color.0 = palette.color(Role::Background);
+}
+
+fn paint_themed(
+    palette: Res<Palette>,
+    mut query: Query<
+        (
+            Ref<Themed>,
+            Option<&mut Sprite>,
+            Option<&mut BackgroundColor>,
+            Option<&mut Text>,
+        ),
+        Without<Interaction>,
+    >,
+    mut button_query: Query<(Ref<Themed>, &mut BackgroundColor, &Interaction)>,
+) {
+    let repaint = palette.is_changed();
+
+    for (themed, sprite, background, text) in query.iter_mut() {
+        if !repaint && !themed.is_added() {
+            continue;
+        }
+        let color = palette.color(themed.0);
+        if let Some(mut sprite) = sprite {
+            sprite.color = color;
+        }
+        if let Some(mut background) = background {
+            background.0 = color;
+        }
+        if let Some(mut text) = text {
+            for section in text.sections.iter_mut() {
+                section.style.color = color;
+            }
+        }
+    }
+
+    for (themed, mut background, interaction) in button_query.iter_mut() {
+        if repaint || themed.is_added() {
+            background.0 = button_color(&palette, themed.0, interaction);
+        }
+    }
+}
+
+fn button_color(palette: &Palette, role: Role, interaction: &Interaction) -> Color {
+    let color = palette.color(role);
+    match interaction {
+        Interaction::Hovered => *color.clone().set_a(HOVER_ALPHA),
+        _ => color,
+    }
+}
+
+/// Fades themed buttons while hovered.
+fn highlight_buttons(
+    palette: Res<Palette>,
+    mut query: Query<(&Interaction, &Themed, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, themed, mut background) in query.iter_mut() {
+        background.0 = button_color(&palette, themed.0, interaction);
+    }
+}
+
+/// Floor under an arena cell. Tiles have no `Position` so that nothing takes
+/// the cell for occupied.
+#[derive(Component)]
+struct FloorTile {
+    x: i32,
+    y: i32,
+    size: f32,
+}
+
+fn spawn_floor(mut commands: Commands, palette: Res<Palette>, arena: Res<Arena>) {
+    let size = match palette.floor {
+        FloorStyle::Plain => return,
+        FloorStyle::Checker => 1.0,
+        FloorStyle::Grid => GRID_TILE,
+    };
+
+    for x in 0..arena.width as i32 {
+        for y in 0..arena.height as i32 {
+            let role = if palette.floor == FloorStyle::Checker && (x + y) % 2 == 1 {
+                Role::FloorAlt
+            } else {
+                Role::Floor
+            };
+            commands.spawn((
+                SpriteBundle {
+                    // under everything else in the arena
+                    transform: Transform::from_xyz(0.0, 0.0, -2.0),
+                    ..default()
+                },
+                Themed(role),
+                FloorTile { x, y, size },
+                RoundCleanup,
+            ));
+        }
+    }
+}
+
+/// The floor style may change with the theme, so the floor is laid again.
+fn despawn_floor(mut commands: Commands, floor_query: Query<Entity, With<FloorTile>>) {
+    for tile in floor_query.iter() {
+        commands.entity(tile).despawn();
+    }
+}
+
+fn position_floor(
+    arena: Res<Arena>,
+    primary_query: Query<&Window, With<PrimaryWindow>>,
+    mut floor_query: Query<(&FloorTile, &mut Transform)>,
+) {
+    let Ok(window) = primary_query.get_single() else {
+        return;
+    };
+    let tile = tile_size(window, &arena);
+
+    for (floor, mut transform) in floor_query.iter_mut() {
+        let translation = arena_to_world(Vec2::new(floor.x as f32, floor.y as f32), window, &arena);
+        transform.translation = translation.extend(transform.translation.z);
+        transform.scale = (tile * floor.size).extend(1.0);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const THEME_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Theme file being watched and its last seen modification time.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Resource)]
+struct ThemeWatch {
+    timer: Timer,
+    path: Option<bevy::asset::AssetPath<'static>>,
+    modified: Option<std::time::SystemTime>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for ThemeWatch {
+    fn default() -> Self {
+        Self {
+            timer: Timer::new(THEME_POLL_INTERVAL, TimerMode::Repeating),
+            path: None,
+            modified: None,
+        }
+    }
+}
+
+/// Polls the selected theme file and reloads it once edited, for builds
+/// without Bevy's file watcher.
+#[cfg(not(target_arch = "wasm32"))]
+fn watch_theme(
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    theme_assets: Res<ThemeAssets>,
+    themes: Res<Assets<Theme>>,
+    settings: Res<ThemeSettings>,
+    mut watch: ResMut<ThemeWatch>,
+) {
+    if !watch.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let Some(path) = selected_theme(&theme_assets, &themes, &settings)
+        .and_then(|handle| asset_server.get_path(handle))
+    else {
+        return;
+    };
+    let modified = std::fs::metadata(std::path::Path::new("assets").join(path.path()))
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    // a newly selected theme is only compared with itself from the next poll
+    if watch.path.as_ref() == Some(&path) && modified != watch.modified {
+        info!("Reloading theme {path}");
+        asset_server.reload(path.clone());
+    }
+    watch.path = Some(path.into_owned());
+    watch.modified = modified;
+}
@@ -0,0 +1,84 @@
+//! Light cycles: there is no food and every step each snake leaves its tail
+//! behind, so trails never retract. Collisions and game over are the usual
+//! grid rules; the last snake moving wins.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{
+        movement_tick, spawn_body, Direction, HighScore, Player, Position, SnakeBody, SnakeHead,
+        SnakeStep,
+    },
+    level::{CurrentLevel, Level, LevelRules},
+    scoring::ScoreBoard,
+    theme::Palette,
+    GameMode, RoundState,
+};
+
+const ARENA_SIZE: i32 = 24;
+const MOVEMENT_INTERVAL: Duration = Duration::from_millis(150);
+
+pub struct TronPlugin;
+
+impl Plugin for TronPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            lay_trails
+                .after(SnakeStep)
+                .run_if(movement_tick)
+                .run_if(in_state(RoundState::Playing))
+                .run_if(tron_round),
+        );
+    }
+}
+
+/// The empty arena light cycle rounds are played in.
+pub(crate) fn arena() -> Level {
+    Level {
+        name: "Tron".to_string(),
+        width: ARENA_SIZE as u32,
+        height: ARENA_SIZE as u32,
+        rules: LevelRules {
+            movement_interval: MOVEMENT_INTERVAL,
+            ..LevelRules::default()
+        },
+        spawn: Position {
+            x: ARENA_SIZE / 2,
+            y: ARENA_SIZE / 2,
+        },
+        facing: Direction::Up,
+        ..Level::default()
+    }
+}
+
+/// Levels picked from the campaign or the editor keep their food.
+pub(crate) fn tron_round(mode: Res<GameMode>, current_level: Res<CurrentLevel>) -> bool {
+    *mode == GameMode::Tron && current_level.0.is_none()
+}
+
+/// Grows every snake back to where its tail was before the step. The first
+/// player scores a point for each cell travelled.
+fn lay_trails(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    mut scoreboard: ResMut<ScoreBoard>,
+    mut high_score: ResMut<HighScore>,
+    mut snake_query: Query<(&SnakeHead, &Player, &mut SnakeBody)>,
+) {
+    for (head, player, mut body) in snake_query.iter_mut() {
+        let Some(tail) = head.tail() else {
+            continue;
+        };
+        body.push(spawn_body(&mut commands, tail, *player, &palette));
+
+        if player.0 == 0 {
+            scoreboard.award(1);
+            if scoreboard.total() > high_score.0 {
+                high_score.0 = scoreboard.total();
+            }
+        }
+    }
+}